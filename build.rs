@@ -0,0 +1,29 @@
+fn main() {
+	println!("cargo:rustc-check-cfg=cfg(fontconfig)");
+
+	// Fonts are registered through fontconfig and the fontconfig backend of pango directly.
+	// Pango doesn't use fontconfig on Windows and macOS, so font registration is not available there.
+	let target_os = std::env::var("CARGO_CFG_TARGET_OS").unwrap_or_default();
+	if target_os != "windows" && target_os != "macos" {
+		probe("fontconfig", None);
+		probe("pangoft2", None);
+		println!("cargo:rustc-cfg=fontconfig");
+	}
+
+	// Overlines were added in pango 1.46, which is newer than what the pango bindings support.
+	if std::env::var_os("CARGO_FEATURE_OVERLINE").is_some() {
+		probe("pango", Some("1.46"));
+	}
+}
+
+/// Find a library with pkg-config, or fail the build with a readable message.
+fn probe(name: &str, min_version: Option<&str>) {
+	let mut config = pkg_config::Config::new();
+	if let Some(version) = min_version {
+		config.atleast_version(version);
+	}
+	if let Err(e) = config.probe(name) {
+		eprintln!("failed to find {} with pkg-config:\n{}", name, e);
+		std::process::exit(1);
+	}
+}
@@ -1,9 +1,10 @@
-use pdf_writer::{BoxPosition, Margins, PdfWriter, TableBuilder, TextStyle, FontSpec, TextAlign, A4, mm, pt};
+use pdf_writer::{BoxPosition, Margins, Metadata, PdfWriter, TableBorders, TableBuilder, TextStyle, FontSpec, TextAlign, A4, mm, pt};
 
 fn main () {
 	let file = std::io::BufWriter::new(std::fs::File::create("foo.pdf").unwrap());
 	let margins = Margins::vh(mm(30.0), mm(20.0));
 	let mut writer = PdfWriter::new(file).unwrap();
+	writer.set_metadata(&Metadata::new().title("Lorem ipsum")).unwrap();
 
 	let p1 = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo consequat. Duis aute irure dolor in reprehenderit in voluptate velit esse cillum dolore eu fugiat nulla pariatur. Excepteur sint occaecat cupidatat non proident, sunt in culpa qui officia deserunt mollit anim id est laborum.";
 	let p2 = "In semper sapien quis ligula egestas auctor. Nulla eget imperdiet urna. Aliquam viverra, lacus nec egestas pharetra, nulla magna iaculis libero, et suscipit magna tortor et metus. Aliquam erat volutpat. Suspendisse pellentesque ante ut arcu pharetra ultricies. Ut finibus vel nulla ac hendrerit. Morbi non ligula a mauris vulputate pulvinar non ut tortor. Proin elit velit, mollis nec risus at, tincidunt sagittis elit. Maecenas vel faucibus massa, nec consectetur mauris. Sed elementum velit maximus, porta nulla eu, aliquam tellus. Quisque eleifend sem ipsum, ac bibendum turpis venenatis at. Nunc condimentum nibh sit amet eros congue, a ornare odio consequat.";
@@ -13,9 +14,8 @@ fn main () {
 
 	let plain = TextStyle {
 		font: FontSpec::plain("serif", pt(10.0)),
-		align: TextAlign::Left,
 		justify: true,
-		line_height: 1.0,
+		.. Default::default()
 	};
 
 	let heading = TextStyle {
@@ -23,19 +23,32 @@ fn main () {
 		.. plain
 	};
 
+	let footer = TextStyle {
+		font: FontSpec::plain("serif", pt(8.0)),
+		align: TextAlign::Center,
+		.. plain.clone()
+	};
+	writer.set_header_footer(move |page, info| {
+		let area = page.footer_area();
+		let text = format!("Page {} of {}", info.number(), info.count);
+		let position = BoxPosition::at(area.center()).anchor_middle_x().anchor_middle_y();
+		page.draw_text_box(&text, &footer, position, None)?;
+		Ok(())
+	});
+
 	let mut page = writer.page(A4, margins).unwrap();
-	page.write_text("PDF writer using cairo/pango", &heading).unwrap();
-	page.write_text("", &plain).unwrap();
-	page.write_text(p1, &plain).unwrap();
-	page.write_text("", &plain).unwrap();
-	page.write_text(p2, &plain).unwrap();
-	page.write_text("", &plain).unwrap();
-	page.write_text(p3, &plain).unwrap();
-	page.write_text("", &plain).unwrap();
-	page.write_text(p4, &plain).unwrap();
-	page.write_text("", &plain).unwrap();
-	page.write_text(p5, &plain).unwrap();
-	page.write_text("", &plain).unwrap();
+	page.write_text(&writer, "PDF writer using cairo/pango", &heading).unwrap();
+	page.write_text(&writer, "", &plain).unwrap();
+	page.write_text(&writer, p1, &plain).unwrap();
+	page.write_text(&writer, "", &plain).unwrap();
+	page.write_text(&writer, p2, &plain).unwrap();
+	page.write_text(&writer, "", &plain).unwrap();
+	page.write_text(&writer, p3, &plain).unwrap();
+	page.write_text(&writer, "", &plain).unwrap();
+	page.write_text(&writer, p4, &plain).unwrap();
+	page.write_text(&writer, "", &plain).unwrap();
+	page.write_text(&writer, p5, &plain).unwrap();
+	page.write_text(&writer, "", &plain).unwrap();
 
 	let plain = TextStyle {
 		line_height: 1.1,
@@ -48,7 +61,8 @@ fn main () {
 	};
 
 	let mut table = TableBuilder::new(&writer, page.text_width());
-	table.position(BoxPosition::at(page.line_center()).anchor_hcenter());
+	table.position(BoxPosition::at(page.line_center()).anchor_middle_x());
+	table.borders(TableBorders::booktabs(1));
 	table.add_column(false, None);
 	table.add_column(false, None);
 	table.add_column(false, None);
@@ -68,7 +82,7 @@ fn main () {
 
 	let table = table.build();
 	table.draw(&page);
-	table.draw_horizontal_border(&page, 1, .., pt(0.1));
 
 	page.emit(&writer).unwrap();
+	writer.finish().unwrap();
 }
@@ -0,0 +1,69 @@
+/// An RGBA color.
+///
+/// All components are in the range 0.0 to 1.0.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Color {
+	pub red: f64,
+	pub green: f64,
+	pub blue: f64,
+	pub alpha: f64,
+}
+
+impl Color {
+	pub const BLACK: Self = Self::rgb(0.0, 0.0, 0.0);
+	pub const WHITE: Self = Self::rgb(1.0, 1.0, 1.0);
+
+	/// Create a fully opaque color from red, green and blue components.
+	pub const fn rgb(red: f64, green: f64, blue: f64) -> Self {
+		Self::rgba(red, green, blue, 1.0)
+	}
+
+	/// Create a color from red, green, blue and alpha components.
+	pub const fn rgba(red: f64, green: f64, blue: f64, alpha: f64) -> Self {
+		Self { red, green, blue, alpha }
+	}
+
+	/// Create a fully opaque color from 8-bit red, green and blue components.
+	pub fn rgb8(red: u8, green: u8, blue: u8) -> Self {
+		Self::rgb(
+			f64::from(red) / 255.0,
+			f64::from(green) / 255.0,
+			f64::from(blue) / 255.0,
+		)
+	}
+
+	/// Create a fully opaque gray color.
+	///
+	/// A value of 0.0 gives black and a value of 1.0 gives white.
+	pub const fn gray(value: f64) -> Self {
+		Self::rgb(value, value, value)
+	}
+
+	/// Get the same color with a different alpha value.
+	pub const fn with_alpha(self, alpha: f64) -> Self {
+		Self { alpha, ..self }
+	}
+
+	/// Set the color as source of a cairo context.
+	pub(crate) fn set_as_source(self, cairo: &cairo::Context) {
+		cairo.set_source_rgba(self.red, self.green, self.blue, self.alpha);
+	}
+
+	/// Get the red, green and blue components as 16-bit values for pango.
+	pub(crate) fn to_pango_rgb(self) -> (u16, u16, u16) {
+		(
+			component_to_u16(self.red),
+			component_to_u16(self.green),
+			component_to_u16(self.blue),
+		)
+	}
+
+	/// Get the alpha component as 16-bit value for pango.
+	pub(crate) fn to_pango_alpha(self) -> u16 {
+		component_to_u16(self.alpha)
+	}
+}
+
+fn component_to_u16(value: f64) -> u16 {
+	(value.clamp(0.0, 1.0) * f64::from(u16::MAX)).round() as u16
+}
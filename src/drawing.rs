@@ -0,0 +1,410 @@
+use crate::{
+	Box2,
+	Color,
+	Length,
+	Mm,
+	Page,
+	Point2,
+	Pt,
+	PT_PER_MM,
+	Size2,
+	Vector2,
+	pt,
+};
+
+/// Distance of the control points for approximating a quarter circle with a cubic Bézier curve.
+const KAPPA: f64 = 0.552_284_749_830_793_4;
+
+/// The style for stroking lines and the outlines of shapes.
+#[derive(Debug, Clone)]
+pub struct StrokeStyle {
+	pub width: Length<Pt>,
+	pub color: Color,
+
+	/// Alternating lengths of dashes and gaps.
+	///
+	/// An empty dash pattern draws a solid line.
+	/// Invalid patterns, with negative or non-finite lengths or with only zero lengths, also draw a solid line.
+	pub dash: Vec<Length<Pt>>,
+
+	/// Offset into the dash pattern at which the stroke starts.
+	pub dash_offset: Length<Pt>,
+
+	pub cap: LineCap,
+	pub join: LineJoin,
+}
+
+impl StrokeStyle {
+	/// Create a solid stroke style with the given width and color.
+	pub fn new(width: Length<Pt>, color: Color) -> Self {
+		Self {
+			width,
+			color,
+			dash: Vec::new(),
+			dash_offset: pt(0.0),
+			cap: LineCap::default(),
+			join: LineJoin::default(),
+		}
+	}
+
+	/// Use a dash pattern of alternating dash and gap lengths.
+	pub fn dashed(self, dash: Vec<Length<Pt>>) -> Self {
+		Self {
+			dash,
+			..self
+		}
+	}
+
+	/// Set the line cap.
+	pub fn cap(self, cap: LineCap) -> Self {
+		Self {
+			cap,
+			..self
+		}
+	}
+
+	/// Set the line join.
+	pub fn join(self, join: LineJoin) -> Self {
+		Self {
+			join,
+			..self
+		}
+	}
+
+	pub(crate) fn apply_to_cairo(&self, cairo: &cairo::Context) {
+		self.color.set_as_source(cairo);
+		cairo.set_line_width(self.width.get());
+		cairo.set_dash(&self.cairo_dash(), self.dash_offset.get());
+		cairo.set_line_cap(self.cap.to_cairo());
+		cairo.set_line_join(self.join.to_cairo());
+	}
+
+	/// Get the dash pattern to pass to cairo.
+	///
+	/// Cairo puts the context in an error state for invalid patterns,
+	/// which would silently drop all further drawing on the page, so they are replaced by a solid line.
+	fn cairo_dash(&self) -> Vec<f64> {
+		let dash: Vec<f64> = self.dash.iter().map(|x| x.get()).collect();
+		let valid = dash.iter().all(|x| x.is_finite() && *x >= 0.0) && dash.iter().any(|x| *x > 0.0);
+		if valid {
+			dash
+		} else {
+			Vec::new()
+		}
+	}
+}
+
+impl std::default::Default for StrokeStyle {
+	fn default() -> Self {
+		Self::new(pt(1.0), Color::BLACK)
+	}
+}
+
+/// The shape used at the end of stroked lines.
+#[derive(Debug, Copy, Clone, Default)]
+pub enum LineCap {
+	/// Stop the line exactly at the end point.
+	#[default]
+	Butt,
+
+	/// End the line with a half circle around the end point.
+	Round,
+
+	/// End the line with a half square around the end point.
+	Square,
+}
+
+impl LineCap {
+	pub(crate) fn to_cairo(self) -> cairo::LineCap {
+		match self {
+			Self::Butt => cairo::LineCap::Butt,
+			Self::Round => cairo::LineCap::Round,
+			Self::Square => cairo::LineCap::Square,
+		}
+	}
+}
+
+/// The shape used where two segments of a stroked line meet.
+#[derive(Debug, Copy, Clone, Default)]
+pub enum LineJoin {
+	/// Join segments with a sharp corner.
+	#[default]
+	Miter,
+
+	/// Join segments with a rounded corner.
+	Round,
+
+	/// Join segments with a cut-off corner.
+	Bevel,
+}
+
+impl LineJoin {
+	pub(crate) fn to_cairo(self) -> cairo::LineJoin {
+		match self {
+			Self::Miter => cairo::LineJoin::Miter,
+			Self::Round => cairo::LineJoin::Round,
+			Self::Bevel => cairo::LineJoin::Bevel,
+		}
+	}
+}
+
+/// The style for drawing shapes.
+///
+/// A shape can be filled, stroked, or both.
+/// The fill is drawn below the stroke.
+#[derive(Debug, Clone, Default)]
+pub struct ShapeStyle {
+	pub stroke: Option<StrokeStyle>,
+	pub fill: Option<Color>,
+}
+
+impl ShapeStyle {
+	/// Create a style that only strokes the outline of shapes.
+	pub fn stroke(stroke: StrokeStyle) -> Self {
+		Self {
+			stroke: Some(stroke),
+			fill: None,
+		}
+	}
+
+	/// Create a style that only fills shapes.
+	pub fn fill(color: Color) -> Self {
+		Self {
+			stroke: None,
+			fill: Some(color),
+		}
+	}
+
+	/// Also stroke the outline of shapes.
+	pub fn with_stroke(self, stroke: StrokeStyle) -> Self {
+		Self {
+			stroke: Some(stroke),
+			..self
+		}
+	}
+
+	/// Also fill shapes.
+	pub fn with_fill(self, color: Color) -> Self {
+		Self {
+			fill: Some(color),
+			..self
+		}
+	}
+}
+
+/// A path made of straight lines and cubic Bézier curves.
+#[derive(Debug, Clone, Default)]
+pub struct Path {
+	segments: Vec<PathSegment>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum PathSegment {
+	MoveTo(Point2<Mm>),
+	LineTo(Point2<Mm>),
+	CurveTo(Point2<Mm>, Point2<Mm>, Point2<Mm>),
+	Close,
+}
+
+impl Path {
+	/// Create a new empty path.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Create a path for a straight line.
+	pub fn line(from: Point2<Mm>, to: Point2<Mm>) -> Self {
+		let mut path = Self::new();
+		path.move_to(from).line_to(to);
+		path
+	}
+
+	/// Create a path through a sequence of points.
+	pub fn polyline(points: &[Point2<Mm>]) -> Self {
+		let mut path = Self::new();
+		if let Some((&first, rest)) = points.split_first() {
+			path.move_to(first);
+			for &point in rest {
+				path.line_to(point);
+			}
+		}
+		path
+	}
+
+	/// Create a path for a rectangle.
+	pub fn rectangle(area: Box2<Mm>) -> Self {
+		let mut path = Self::new();
+		path.move_to(area.min)
+			.line_to(Point2::new(area.max.x, area.min.y))
+			.line_to(area.max)
+			.line_to(Point2::new(area.min.x, area.max.y))
+			.close();
+		path
+	}
+
+	/// Create a path for a rectangle with rounded corners.
+	///
+	/// The radius is limited to half the width and height of the rectangle.
+	pub fn rounded_rectangle(area: Box2<Mm>, radius: Length<Mm>) -> Self {
+		let r = radius.get().min(area.width() * 0.5).min(area.height() * 0.5).max(0.0);
+		let k = r * (1.0 - KAPPA);
+		let (x0, y0, x1, y1) = (area.min.x, area.min.y, area.max.x, area.max.y);
+
+		let mut path = Self::new();
+		path.move_to(Point2::new(x0 + r, y0))
+			.line_to(Point2::new(x1 - r, y0))
+			.curve_to(Point2::new(x1 - k, y0), Point2::new(x1, y0 + k), Point2::new(x1, y0 + r))
+			.line_to(Point2::new(x1, y1 - r))
+			.curve_to(Point2::new(x1, y1 - k), Point2::new(x1 - k, y1), Point2::new(x1 - r, y1))
+			.line_to(Point2::new(x0 + r, y1))
+			.curve_to(Point2::new(x0 + k, y1), Point2::new(x0, y1 - k), Point2::new(x0, y1 - r))
+			.line_to(Point2::new(x0, y0 + r))
+			.curve_to(Point2::new(x0, y0 + k), Point2::new(x0 + k, y0), Point2::new(x0 + r, y0))
+			.close();
+		path
+	}
+
+	/// Create a path for an ellipse with the given horizontal and vertical radius.
+	pub fn ellipse(center: Point2<Mm>, radii: Size2<Mm>) -> Self {
+		let rx = Vector2::new(radii.width, 0.0);
+		let ry = Vector2::new(0.0, radii.height);
+		let kx = rx * KAPPA;
+		let ky = ry * KAPPA;
+
+		let mut path = Self::new();
+		path.move_to(center + rx)
+			.curve_to(center + rx + ky, center + kx + ry, center + ry)
+			.curve_to(center - kx + ry, center - rx + ky, center - rx)
+			.curve_to(center - rx - ky, center - kx - ry, center - ry)
+			.curve_to(center + kx - ry, center + rx - ky, center + rx)
+			.close();
+		path
+	}
+
+	/// Create a path for a circle.
+	pub fn circle(center: Point2<Mm>, radius: Length<Mm>) -> Self {
+		Self::ellipse(center, Size2::new(radius.get(), radius.get()))
+	}
+
+	/// Start a new sub-path at the given point.
+	pub fn move_to(&mut self, point: Point2<Mm>) -> &mut Self {
+		self.segments.push(PathSegment::MoveTo(point));
+		self
+	}
+
+	/// Add a straight line from the current point to the given point.
+	pub fn line_to(&mut self, point: Point2<Mm>) -> &mut Self {
+		self.segments.push(PathSegment::LineTo(point));
+		self
+	}
+
+	/// Add a cubic Bézier curve from the current point to `end`.
+	pub fn curve_to(&mut self, control1: Point2<Mm>, control2: Point2<Mm>, end: Point2<Mm>) -> &mut Self {
+		self.segments.push(PathSegment::CurveTo(control1, control2, end));
+		self
+	}
+
+	/// Close the current sub-path with a straight line to its start point.
+	pub fn close(&mut self) -> &mut Self {
+		self.segments.push(PathSegment::Close);
+		self
+	}
+
+	pub(crate) fn append_to_cairo(&self, cairo: &cairo::Context) {
+		for segment in &self.segments {
+			match *segment {
+				PathSegment::MoveTo(point) => {
+					let point = point * PT_PER_MM;
+					cairo.move_to(point.x, point.y);
+				},
+				PathSegment::LineTo(point) => {
+					let point = point * PT_PER_MM;
+					cairo.line_to(point.x, point.y);
+				},
+				PathSegment::CurveTo(control1, control2, end) => {
+					let control1 = control1 * PT_PER_MM;
+					let control2 = control2 * PT_PER_MM;
+					let end = end * PT_PER_MM;
+					cairo.curve_to(control1.x, control1.y, control2.x, control2.y, end.x, end.y);
+				},
+				PathSegment::Close => cairo.close_path(),
+			}
+		}
+	}
+}
+
+impl Page {
+	/// Draw a straight line.
+	pub fn draw_line(&self, from: Point2<Mm>, to: Point2<Mm>, stroke: &StrokeStyle) {
+		self.draw_path(&Path::line(from, to), &ShapeStyle::stroke(stroke.clone()));
+	}
+
+	/// Draw a line through a sequence of points.
+	pub fn draw_polyline(&self, points: &[Point2<Mm>], stroke: &StrokeStyle) {
+		self.draw_path(&Path::polyline(points), &ShapeStyle::stroke(stroke.clone()));
+	}
+
+	/// Draw a rectangle.
+	pub fn draw_rectangle(&self, area: Box2<Mm>, style: &ShapeStyle) {
+		self.draw_path(&Path::rectangle(area), style);
+	}
+
+	/// Draw a rectangle with rounded corners.
+	pub fn draw_rounded_rectangle(&self, area: Box2<Mm>, radius: Length<Mm>, style: &ShapeStyle) {
+		self.draw_path(&Path::rounded_rectangle(area, radius), style);
+	}
+
+	/// Draw a circle.
+	pub fn draw_circle(&self, center: Point2<Mm>, radius: Length<Mm>, style: &ShapeStyle) {
+		self.draw_path(&Path::circle(center, radius), style);
+	}
+
+	/// Draw an ellipse with the given horizontal and vertical radius.
+	pub fn draw_ellipse(&self, center: Point2<Mm>, radii: Size2<Mm>, style: &ShapeStyle) {
+		self.draw_path(&Path::ellipse(center, radii), style);
+	}
+
+	/// Draw an arbitrary path.
+	pub fn draw_path(&self, path: &Path, style: &ShapeStyle) {
+		self.cairo.save();
+		self.cairo.new_path();
+		path.append_to_cairo(&self.cairo);
+		if let Some(fill) = style.fill {
+			fill.set_as_source(&self.cairo);
+			self.cairo.fill_preserve();
+		}
+		if let Some(stroke) = &style.stroke {
+			stroke.apply_to_cairo(&self.cairo);
+			self.cairo.stroke();
+		}
+		self.cairo.new_path();
+		self.cairo.restore();
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn dash(lengths: &[f64]) -> Vec<f64> {
+		StrokeStyle::default()
+			.dashed(lengths.iter().map(|&x| pt(x)).collect())
+			.cairo_dash()
+	}
+
+	#[test]
+	fn valid_dash_patterns() {
+		assert_eq!(dash(&[]), Vec::<f64>::new());
+		assert_eq!(dash(&[2.0, 1.0]), [2.0, 1.0]);
+		assert_eq!(dash(&[0.0, 3.0]), [0.0, 3.0]);
+	}
+
+	#[test]
+	fn invalid_dash_patterns_are_solid() {
+		assert_eq!(dash(&[2.0, -1.0]), Vec::<f64>::new());
+		assert_eq!(dash(&[0.0, 0.0]), Vec::<f64>::new());
+		assert_eq!(dash(&[0.0]), Vec::<f64>::new());
+		assert_eq!(dash(&[f64::NAN, 1.0]), Vec::<f64>::new());
+		assert_eq!(dash(&[f64::INFINITY]), Vec::<f64>::new());
+	}
+}
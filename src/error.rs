@@ -0,0 +1,128 @@
+/// Error that can occur while writing a PDF document.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+	/// Failed to create a cairo surface.
+	CreateSurface(cairo::Error),
+
+	/// Failed to get the default font map.
+	GetFontMap,
+
+	/// Failed to load a font.
+	LoadFont {
+		/// The family of the font that failed to load.
+		family: String,
+	},
+
+	/// A font family is not the alias of a registered font.
+	UnknownFont {
+		/// The unknown alias.
+		alias: String,
+	},
+
+	/// A font resolved to a different family than requested.
+	FontSubstituted {
+		/// The requested font family.
+		requested: String,
+
+		/// The family of the font that would have been used instead.
+		resolved: String,
+	},
+
+	/// Failed to register a font file.
+	AddFont {
+		/// The path of the font file.
+		path: std::path::PathBuf,
+	},
+
+	/// A font file has a different family than the fonts already registered under the same alias.
+	FontAliasConflict {
+		/// The alias the font was registered under.
+		alias: String,
+
+		/// The family of the fonts already registered under the alias.
+		registered: String,
+
+		/// The family of the new font file.
+		family: String,
+	},
+
+	/// Failed to create or inspect a pango text layout.
+	CreateLayout,
+
+	/// Failed to parse pango markup.
+	ParseMarkup(glib::Error),
+
+	/// Failed to set the size of a page.
+	SetPageSize(cairo::Error),
+
+	/// Failed to set document metadata.
+	SetMetadata(cairo::Error),
+
+	/// Failed to add an entry to the document outline.
+	AddOutline(cairo::Error),
+
+	/// Failed to decode a PNG image.
+	DecodePng(cairo::IoError),
+
+	/// The image data is not a valid or supported image.
+	InvalidImage(&'static str),
+
+	/// The SVG data could not be parsed.
+	InvalidSvg(String),
+
+	/// An I/O error occurred while reading input files or writing to the output stream.
+	Io(std::io::Error),
+}
+
+impl std::fmt::Display for Error {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		match self {
+			Self::CreateSurface(e) => write!(f, "failed to create surface: {}", e),
+			Self::GetFontMap => write!(f, "failed to get default font map"),
+			Self::LoadFont { family } => write!(f, "failed to load font: {}", family),
+			Self::UnknownFont { alias } => write!(f, "no font registered with alias: {}", alias),
+			Self::FontSubstituted { requested, resolved } => write!(f, "font {} was substituted by {}", requested, resolved),
+			Self::AddFont { path } => write!(f, "failed to add font file: {}", path.display()),
+			Self::FontAliasConflict { alias, registered, family } => write!(f, "font alias {} already refers to {}, not {}", alias, registered, family),
+			Self::CreateLayout => write!(f, "failed to create pango layout"),
+			Self::ParseMarkup(e) => write!(f, "failed to parse markup: {}", e),
+			Self::SetPageSize(e) => write!(f, "failed to set page size: {}", e),
+			Self::SetMetadata(e) => write!(f, "failed to set document metadata: {}", e),
+			Self::AddOutline(e) => write!(f, "failed to add outline entry: {}", e),
+			Self::DecodePng(e) => write!(f, "failed to decode PNG image: {}", e),
+			Self::InvalidImage(e) => write!(f, "invalid image: {}", e),
+			Self::InvalidSvg(e) => write!(f, "invalid SVG: {}", e),
+			Self::Io(e) => write!(f, "I/O error: {}", e),
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Self::CreateSurface(e) => Some(e),
+			Self::GetFontMap => None,
+			Self::LoadFont { .. } => None,
+			Self::UnknownFont { .. } => None,
+			Self::FontSubstituted { .. } => None,
+			Self::AddFont { .. } => None,
+			Self::FontAliasConflict { .. } => None,
+			Self::CreateLayout => None,
+			Self::ParseMarkup(e) => Some(e),
+			Self::SetPageSize(e) => Some(e),
+			Self::SetMetadata(e) => Some(e),
+			Self::AddOutline(e) => Some(e),
+			Self::DecodePng(e) => Some(e),
+			Self::InvalidImage(_) => None,
+			Self::InvalidSvg(_) => None,
+			Self::Io(e) => Some(e),
+		}
+	}
+}
+
+impl From<std::io::Error> for Error {
+	fn from(other: std::io::Error) -> Self {
+		Self::Io(other)
+	}
+}
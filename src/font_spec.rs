@@ -3,9 +3,32 @@ use crate::{Length, Pt};
 #[derive(Debug, Clone)]
 pub struct FontSpec {
 	pub family: String,
+
+	/// Font families to use for characters that are missing from the main family, in order of preference.
+	pub fallbacks: Vec<String>,
+
 	pub size: Length<Pt>,
 	pub weight: FontWeight,
 	pub style: FontStyle,
+
+	/// The width of the font.
+	pub stretch: FontStretch,
+
+	/// The variant of the font.
+	pub variant: FontVariant,
+
+	/// OpenType features to enable or disable.
+	pub features: Vec<FontFeature>,
+
+	/// Values for the axes of a variable font.
+	pub variations: Vec<FontVariation>,
+}
+
+impl Default for FontSpec {
+	/// Get a plain 10 point sans-serif font.
+	fn default() -> Self {
+		Self::plain("sans-serif", crate::pt(10.0))
+	}
 }
 
 impl FontSpec {
@@ -13,9 +36,14 @@ impl FontSpec {
 		let family = family.into();
 		Self {
 			family,
+			fallbacks: Vec::new(),
 			size,
 			weight,
 			style,
+			stretch: FontStretch::Normal,
+			variant: FontVariant::Normal,
+			features: Vec::new(),
+			variations: Vec::new(),
 		}
 	}
 
@@ -37,23 +65,76 @@ impl FontSpec {
 		)
 	}
 
+	/// Add a fallback family for characters that are missing from the earlier families.
+	///
+	/// Fallbacks are tried in the order they were added,
+	/// before any substitutions configured on the system.
+	pub fn with_fallback(mut self, family: impl Into<String>) -> Self {
+		self.fallbacks.push(family.into());
+		self
+	}
+
+	/// Get the main family followed by the fallback families.
+	pub(crate) fn families(&self) -> impl Iterator<Item = &str> {
+		std::iter::once(self.family.as_str())
+			.chain(self.fallbacks.iter().map(|x| x.as_str()))
+	}
+
+	/// Set the width of the font.
+	pub fn with_stretch(self, stretch: FontStretch) -> Self {
+		Self { stretch, ..self }
+	}
+
+	/// Set the variant of the font.
+	pub fn with_variant(self, variant: FontVariant) -> Self {
+		Self { variant, ..self }
+	}
+
+	/// Add an OpenType feature.
+	pub fn with_feature(mut self, feature: FontFeature) -> Self {
+		self.features.push(feature);
+		self
+	}
+
+	/// Add a value for an axis of a variable font.
+	pub fn with_variation(mut self, variation: FontVariation) -> Self {
+		self.variations.push(variation);
+		self
+	}
+
 	pub(crate) fn to_pango(&self) -> pango::FontDescription {
 		let mut font = pango::FontDescription::new();
 		font.set_family(&self.family);
 		font.set_weight(self.weight.to_pango());
 		font.set_style(self.style.to_pango());
+		font.set_stretch(self.stretch.to_pango());
+		font.set_variant(self.variant.to_pango());
 		font.set_absolute_size((self.size * crate::PANGO_PER_PT).get());
+		if !self.variations.is_empty() {
+			let variations: Vec<_> = self.variations.iter().map(|x| x.to_string()).collect();
+			font.set_variations(&variations.join(","));
+		}
 		font
 	}
+
+	/// Get the OpenType features in the syntax of the pango font features attribute.
+	pub(crate) fn features_string(&self) -> Option<String> {
+		if self.features.is_empty() {
+			return None;
+		}
+		let features: Vec<_> = self.features.iter().map(|x| x.to_string()).collect();
+		Some(features.join(", "))
+	}
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub enum FontWeight {
 	Thin,
 	UltraLight,
 	Light,
 	SemiLight,
 	Book,
+	#[default]
 	Normal,
 	Medium,
 	SemiBold,
@@ -82,14 +163,9 @@ impl FontWeight {
 	}
 }
 
-impl std::default::Default for FontWeight {
-	fn default() -> Self {
-		Self::Normal
-	}
-}
-
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Default)]
 pub enum FontStyle {
+	#[default]
 	Normal,
 	Oblique,
 	Italic,
@@ -105,8 +181,137 @@ impl FontStyle {
 	}
 }
 
-impl std::default::Default for FontStyle {
-	fn default() -> Self {
-		Self::Normal
+#[derive(Debug, Copy, Clone, Default)]
+pub enum FontStretch {
+	UltraCondensed,
+	ExtraCondensed,
+	Condensed,
+	SemiCondensed,
+	#[default]
+	Normal,
+	SemiExpanded,
+	Expanded,
+	ExtraExpanded,
+	UltraExpanded,
+}
+
+impl FontStretch {
+	pub(crate) fn to_pango(self) -> pango::Stretch {
+		match self {
+			Self::UltraCondensed => pango::Stretch::UltraCondensed,
+			Self::ExtraCondensed => pango::Stretch::ExtraCondensed,
+			Self::Condensed => pango::Stretch::Condensed,
+			Self::SemiCondensed => pango::Stretch::SemiCondensed,
+			Self::Normal => pango::Stretch::Normal,
+			Self::SemiExpanded => pango::Stretch::SemiExpanded,
+			Self::Expanded => pango::Stretch::Expanded,
+			Self::ExtraExpanded => pango::Stretch::ExtraExpanded,
+			Self::UltraExpanded => pango::Stretch::UltraExpanded,
+		}
+	}
+}
+
+#[derive(Debug, Copy, Clone, Default)]
+pub enum FontVariant {
+	#[default]
+	Normal,
+	SmallCaps,
+}
+
+impl FontVariant {
+	pub(crate) fn to_pango(self) -> pango::Variant {
+		match self {
+			Self::Normal => pango::Variant::Normal,
+			Self::SmallCaps => pango::Variant::SmallCaps,
+		}
+	}
+}
+
+/// An OpenType feature setting.
+///
+/// The tag is the four letter name of the feature, like `tnum` or `liga`.
+/// A value of 0 disables the feature, 1 enables it,
+/// and higher values select an alternate for features that have multiple alternates.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FontFeature {
+	pub tag: String,
+	pub value: u32,
+}
+
+impl FontFeature {
+	pub fn new(tag: impl Into<String>, value: u32) -> Self {
+		Self { tag: tag.into(), value }
+	}
+
+	/// Enable a feature.
+	pub fn enable(tag: impl Into<String>) -> Self {
+		Self::new(tag, 1)
+	}
+
+	/// Disable a feature.
+	pub fn disable(tag: impl Into<String>) -> Self {
+		Self::new(tag, 0)
+	}
+
+	/// Use tabular figures, where all digits have the same width (`tnum`).
+	pub fn tabular_figures() -> Self {
+		Self::enable("tnum")
+	}
+
+	/// Use old-style figures, which have ascenders and descenders like lowercase letters (`onum`).
+	pub fn oldstyle_figures() -> Self {
+		Self::enable("onum")
+	}
+
+	/// Replace lowercase letters with small capitals (`smcp`).
+	pub fn small_caps() -> Self {
+		Self::enable("smcp")
+	}
+
+	/// Enable or disable standard ligatures (`liga`).
+	pub fn ligatures(enable: bool) -> Self {
+		Self::new("liga", enable.into())
+	}
+}
+
+impl std::fmt::Display for FontFeature {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "\"{}\" {}", self.tag, self.value)
+	}
+}
+
+/// A value for an axis of a variable font.
+///
+/// The axis is the four letter tag of the axis, like `wght` or `opsz`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FontVariation {
+	pub axis: String,
+	pub value: f64,
+}
+
+impl FontVariation {
+	pub fn new(axis: impl Into<String>, value: f64) -> Self {
+		Self { axis: axis.into(), value }
+	}
+
+	/// Set the weight axis (`wght`), typically from 100 to 900.
+	pub fn weight(value: f64) -> Self {
+		Self::new("wght", value)
+	}
+
+	/// Set the width axis (`wdth`), as percentage of the normal width.
+	pub fn width(value: f64) -> Self {
+		Self::new("wdth", value)
+	}
+
+	/// Set the optical size axis (`opsz`), typically in points.
+	pub fn optical_size(value: f64) -> Self {
+		Self::new("opsz", value)
+	}
+}
+
+impl std::fmt::Display for FontVariation {
+	fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+		write!(f, "{}={}", self.axis, self.value)
 	}
 }
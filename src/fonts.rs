@@ -0,0 +1,412 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use pango::{FontExt, FontMapExt};
+
+#[cfg(fontconfig)]
+use std::ffi::{CStr, CString};
+#[cfg(fontconfig)]
+use std::os::raw::{c_char, c_int, c_uint, c_void};
+#[cfg(fontconfig)]
+use std::path::{Path, PathBuf};
+#[cfg(fontconfig)]
+use glib::ObjectType;
+
+use crate::{Error, FontSpec, PdfWriter};
+
+/// How to handle fonts that resolve to a different family than requested.
+///
+/// Font lookup almost never fails outright, because fontconfig substitutes a fallback font instead.
+/// Generic families such as `serif` and `monospace` always resolve to a concrete family,
+/// so they are never considered substituted.
+///
+/// The families of font specifications and [`SpanStyle`](crate::SpanStyle) overrides are checked.
+/// Families in pango markup are not checked.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum FontResolution {
+	/// Silently use the substituted font.
+	#[default]
+	Fallback,
+
+	/// Use the substituted font, but record the substitution.
+	///
+	/// Recorded substitutions can be retrieved with [`PdfWriter::font_substitutions`].
+	Warn,
+
+	/// Fail with [`Error::FontSubstituted`].
+	Strict,
+}
+
+/// A font family that was substituted by a different family.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FontSubstitution {
+	/// The requested font family.
+	pub requested: String,
+
+	/// The family of the font that was used instead.
+	pub resolved: String,
+}
+
+/// A concrete font face used to render text.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FontFace {
+	/// The family of the font.
+	pub family: String,
+
+	/// The full description of the font, including style, weight and size.
+	pub description: String,
+}
+
+impl FontFace {
+	pub(crate) fn from_pango(font: &pango::Font) -> Option<Self> {
+		let description = font.describe()?;
+		Some(Self {
+			family: description.get_family()?.to_string(),
+			description: description.to_string(),
+		})
+	}
+}
+
+/// Shared handle to the fonts of a PDF writer.
+pub(crate) type Fonts = Rc<RefCell<FontRegistry>>;
+
+/// The fonts available for rendering text.
+///
+/// By default, fonts are resolved through the default font map, which uses the fonts installed on the system.
+/// As soon as a font is registered, the registry switches to a private font map that only contains registered fonts,
+/// and font families must refer to the alias of a registered font.
+pub(crate) struct FontRegistry {
+	font_map: pango::FontMap,
+
+	/// The private fontconfig configuration, if any fonts have been registered.
+	config: Option<*mut FcConfig>,
+
+	/// The font family for each registered alias.
+	aliases: HashMap<String, String>,
+
+	/// Private directory holding fonts that were registered from memory.
+	#[cfg(fontconfig)]
+	temp_dir: Option<PathBuf>,
+
+	/// The number of fonts written to the temporary directory.
+	#[cfg(fontconfig)]
+	temp_files: usize,
+
+	/// How to handle substituted fonts.
+	resolution: FontResolution,
+
+	/// The substitutions recorded with [`FontResolution::Warn`].
+	substitutions: Vec<FontSubstitution>,
+}
+
+impl FontRegistry {
+	pub(crate) fn new() -> Result<Fonts, Error> {
+		let font_map = pangocairo::FontMap::get_default()
+			.ok_or(Error::GetFontMap)?;
+		Ok(Rc::new(RefCell::new(Self {
+			font_map,
+			config: None,
+			aliases: HashMap::new(),
+			#[cfg(fontconfig)]
+			temp_dir: None,
+			#[cfg(fontconfig)]
+			temp_files: 0,
+			resolution: FontResolution::default(),
+			substitutions: Vec::new(),
+		})))
+	}
+
+	/// Create a pango layout for drawing on a cairo context.
+	pub(crate) fn create_layout(&self, cairo: &cairo::Context) -> Result<pango::Layout, Error> {
+		let context = self.font_map.create_context()
+			.ok_or(Error::CreateLayout)?;
+		pangocairo::update_context(cairo, &context);
+		Ok(pango::Layout::new(&context))
+	}
+
+	/// Resolve a font family or alias to the family name known to the font map.
+	///
+	/// With a private font map, only the aliases of registered fonts are accepted.
+	fn resolve_family(&self, family: &str) -> Result<String, Error> {
+		if self.config.is_none() {
+			return Ok(family.to_string());
+		}
+		self.aliases.get(family)
+			.cloned()
+			.ok_or_else(|| Error::UnknownFont { alias: family.to_string() })
+	}
+
+	/// Resolve a list of font families to the comma separated family list passed to pango.
+	///
+	/// Each family may itself be a comma separated list of families.
+	pub(crate) fn resolve_families<'a>(&self, families: impl IntoIterator<Item = &'a str>) -> Result<String, Error> {
+		let mut resolved = Vec::new();
+		for family in families.into_iter().flat_map(|x| x.split(',')) {
+			let family = family.trim();
+			if !family.is_empty() {
+				resolved.push(self.resolve_family(family)?);
+			}
+		}
+		Ok(resolved.join(","))
+	}
+
+	/// Get the pango font description for a font specification, with the families resolved.
+	pub(crate) fn describe(&self, font: &FontSpec) -> Result<pango::FontDescription, Error> {
+		let mut description = font.to_pango();
+		description.set_family(&self.resolve_families(font.families())?);
+		Ok(description)
+	}
+
+	/// Check that a loaded font has the requested family, according to the resolution mode.
+	pub(crate) fn check_loaded(&mut self, description: &pango::FontDescription, font: &pango::Font) -> Result<(), Error> {
+		if self.resolution == FontResolution::Fallback {
+			return Ok(());
+		}
+
+		// Only the main family is checked, fallback families are meant for characters missing from it.
+		let requested = description.get_family()
+			.and_then(|x| x.split(',').next().map(|x| x.to_string()))
+			.unwrap_or_default();
+		let resolved = match FontFace::from_pango(font) {
+			Some(face) => face.family,
+			None => return Err(Error::LoadFont { family: requested }),
+		};
+		if resolved.eq_ignore_ascii_case(&requested) || is_generic_family(&requested) {
+			return Ok(());
+		}
+
+		if self.resolution == FontResolution::Strict {
+			return Err(Error::FontSubstituted { requested, resolved });
+		}
+		let substitution = FontSubstitution { requested, resolved };
+		if !self.substitutions.contains(&substitution) {
+			self.substitutions.push(substitution);
+		}
+		Ok(())
+	}
+
+}
+
+/// Font registration, which is only available where pango uses fontconfig.
+#[cfg(fontconfig)]
+impl FontRegistry {
+	/// Register a font file under an alias.
+	fn add_file(&mut self, alias: String, path: &Path) -> Result<(), Error> {
+		let add_font_error = || Error::AddFont { path: path.to_path_buf() };
+		let c_path = path.to_str()
+			.and_then(|x| CString::new(x).ok())
+			.ok_or_else(add_font_error)?;
+
+		// Fontconfig doesn't tell us what it added, so query the family of the first face in the file.
+		let family = unsafe {
+			let mut count = 0;
+			let pattern = FcFreeTypeQuery(c_path.as_ptr(), 0, std::ptr::null_mut(), &mut count);
+			if pattern.is_null() {
+				return Err(add_font_error());
+			}
+			let mut family: *mut c_char = std::ptr::null_mut();
+			let found = FcPatternGetString(pattern, FC_FAMILY.as_ptr() as *const c_char, 0, &mut family) == FC_RESULT_MATCH;
+			let family = found.then(|| CStr::from_ptr(family).to_string_lossy().into_owned());
+			FcPatternDestroy(pattern);
+			family.ok_or_else(add_font_error)?
+		};
+
+		if let Some(registered) = self.aliases.get(&alias) {
+			if *registered != family {
+				return Err(Error::FontAliasConflict {
+					alias,
+					registered: registered.clone(),
+					family,
+				});
+			}
+		}
+
+		let config = self.private_config()?;
+		if unsafe { FcConfigAppFontAddFile(config, c_path.as_ptr()) } == 0 {
+			return Err(add_font_error());
+		}
+
+		// The font map caches fonts, so tell it that the configuration changed.
+		unsafe {
+			pango_fc_font_map_config_changed(self.font_map_ptr());
+		}
+		self.aliases.insert(alias, family);
+		Ok(())
+	}
+
+	/// Register a font from memory under an alias.
+	///
+	/// Fontconfig can only load fonts from files, so the data is written to a file in a private temporary directory.
+	/// The directory is removed when the registry is dropped.
+	fn add_data(&mut self, alias: String, data: &[u8]) -> Result<(), Error> {
+		use std::io::Write;
+
+		let name = format!("font-{}", self.temp_files);
+		let path = self.temp_dir()?.join(name);
+		self.temp_files += 1;
+		let mut file = std::fs::OpenOptions::new()
+			.write(true)
+			.create_new(true)
+			.open(&path)?;
+		file.write_all(data)?;
+		drop(file);
+		self.add_file(alias, &path)
+	}
+
+	/// Get the private temporary directory for fonts registered from memory, creating it if needed.
+	///
+	/// The directory is created with a fresh name and is only accessible by the current user,
+	/// so other users can not replace the font files.
+	fn temp_dir(&mut self) -> Result<&Path, Error> {
+		use std::sync::atomic::{AtomicUsize, Ordering};
+		static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+		if self.temp_dir.is_none() {
+			let mut builder = std::fs::DirBuilder::new();
+			#[cfg(unix)]
+			std::os::unix::fs::DirBuilderExt::mode(&mut builder, 0o700);
+
+			let path = loop {
+				let name = format!("pdf-writer-fonts-{}-{}", std::process::id(), COUNTER.fetch_add(1, Ordering::Relaxed));
+				let path = std::env::temp_dir().join(name);
+				match builder.create(&path) {
+					Ok(()) => break path,
+					Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+					Err(e) => return Err(e.into()),
+				}
+			};
+			self.temp_dir = Some(path);
+		}
+		Ok(self.temp_dir.as_deref().unwrap())
+	}
+
+	/// Get the private fontconfig configuration, switching to a private font map if needed.
+	fn private_config(&mut self) -> Result<*mut FcConfig, Error> {
+		if let Some(config) = self.config {
+			return Ok(config);
+		}
+
+		// Only fontconfig based font maps can be given a fontconfig configuration.
+		let font_map = pangocairo::FontMap::new_for_font_type(cairo::FontType::FontTypeFt)
+			.ok_or(Error::GetFontMap)?;
+		let config = unsafe { FcConfigCreate() };
+		if config.is_null() {
+			return Err(Error::GetFontMap);
+		}
+		self.font_map = font_map;
+		self.config = Some(config);
+		unsafe {
+			// The font map takes its own reference to the configuration.
+			pango_fc_font_map_set_config(self.font_map_ptr(), config);
+		}
+		Ok(config)
+	}
+
+	fn font_map_ptr(&self) -> *mut c_void {
+		self.font_map.as_ptr() as *mut c_void
+	}
+}
+
+#[cfg(fontconfig)]
+impl Drop for FontRegistry {
+	fn drop(&mut self) {
+		if let Some(config) = self.config.take() {
+			unsafe {
+				FcConfigDestroy(config);
+			}
+		}
+		if let Some(path) = &self.temp_dir {
+			let _ = std::fs::remove_dir_all(path);
+		}
+	}
+}
+
+impl<W> PdfWriter<W> {
+	/// Register a font file under an alias.
+	///
+	/// The alias can be used as family in a [`FontSpec`].
+	/// Multiple files can be registered under the same alias to provide different weights and styles of a family.
+	/// All files registered under an alias must have the same family, otherwise [`Error::FontAliasConflict`] is returned.
+	/// TrueType, OpenType and WOFF fonts are supported.
+	///
+	/// Once a font is registered, text is rendered using only registered fonts.
+	/// Using a family that is not the alias of a registered font results in [`Error::UnknownFont`],
+	/// instead of silently falling back to a font installed on the system.
+	/// Font families in pango markup are not resolved as aliases, see [`Text::Markup`](crate::Text::Markup).
+	///
+	/// Font registration is only available on platforms where pango uses fontconfig, so not on Windows and macOS.
+	#[cfg(fontconfig)]
+	pub fn add_font_file(&mut self, alias: impl Into<String>, path: impl AsRef<Path>) -> Result<(), Error> {
+		self.fonts.borrow_mut().add_file(alias.into(), path.as_ref())
+	}
+
+	/// Register a font from memory under an alias.
+	///
+	/// See [`PdfWriter::add_font_file`] for details.
+	#[cfg(fontconfig)]
+	pub fn add_font_data(&mut self, alias: impl Into<String>, data: &[u8]) -> Result<(), Error> {
+		self.fonts.borrow_mut().add_data(alias.into(), data)
+	}
+
+	/// Set how to handle fonts that resolve to a different family than requested.
+	///
+	/// The default is [`FontResolution::Fallback`].
+	pub fn set_font_resolution(&mut self, resolution: FontResolution) {
+		self.fonts.borrow_mut().resolution = resolution;
+	}
+
+	/// Get the font substitutions recorded so far with [`FontResolution::Warn`].
+	///
+	/// Each combination of requested and resolved family is only reported once.
+	pub fn font_substitutions(&self) -> Vec<FontSubstitution> {
+		self.fonts.borrow().substitutions.clone()
+	}
+}
+
+/// Check if a family is a generic family, such as `serif` or `monospace`.
+///
+/// Generic families always resolve to a concrete family, so they are never reported as substituted.
+fn is_generic_family(family: &str) -> bool {
+	const GENERIC: &[&str] = &["serif", "sans-serif", "sans", "monospace", "mono", "cursive", "fantasy", "system-ui", "emoji", "math"];
+	GENERIC.iter().any(|x| x.eq_ignore_ascii_case(family))
+}
+
+enum FcConfig {}
+
+#[cfg(fontconfig)]
+enum FcPattern {}
+
+#[cfg(fontconfig)]
+const FC_RESULT_MATCH: c_int = 0;
+
+#[cfg(fontconfig)]
+const FC_FAMILY: &[u8] = b"family\0";
+
+// Linked through pkg-config by the build script.
+#[cfg(fontconfig)]
+extern "C" {
+	fn FcConfigCreate() -> *mut FcConfig;
+	fn FcConfigDestroy(config: *mut FcConfig);
+	fn FcConfigAppFontAddFile(config: *mut FcConfig, file: *const c_char) -> c_int;
+	fn FcFreeTypeQuery(file: *const c_char, id: c_uint, blanks: *mut c_void, count: *mut c_int) -> *mut FcPattern;
+	fn FcPatternGetString(pattern: *const FcPattern, object: *const c_char, n: c_int, value: *mut *mut c_char) -> c_int;
+	fn FcPatternDestroy(pattern: *mut FcPattern);
+
+	fn pango_fc_font_map_set_config(font_map: *mut c_void, config: *mut FcConfig);
+	fn pango_fc_font_map_config_changed(font_map: *mut c_void);
+}
+
+#[cfg(test)]
+mod test {
+	use super::is_generic_family;
+
+	#[test]
+	fn generic_families() {
+		assert!(is_generic_family("serif"));
+		assert!(is_generic_family("Sans-Serif"));
+		assert!(is_generic_family("monospace"));
+		assert!(!is_generic_family("DejaVu Serif"));
+		assert!(!is_generic_family(""));
+	}
+}
@@ -0,0 +1,112 @@
+use crate::{
+	Box2,
+	Error,
+	Mm,
+	Page,
+	PdfWriter,
+	Point2,
+	PT_PER_MM,
+};
+
+/// Callback that draws the header and footer of a page.
+pub(crate) type HeaderFooterFn = Box<dyn FnMut(&mut Page, PageInfo) -> Result<(), Error>>;
+
+/// Information about a page passed to the header and footer callback.
+#[derive(Debug, Copy, Clone)]
+pub struct PageInfo {
+	/// The zero-based index of the page in the document.
+	pub index: usize,
+
+	/// The total number of pages in the document.
+	pub count: usize,
+}
+
+impl PageInfo {
+	/// Get the one-based page number, suitable for display.
+	pub fn number(&self) -> usize {
+		self.index + 1
+	}
+}
+
+impl<W> PdfWriter<W> {
+	/// Set a callback to draw the header and footer of each page.
+	///
+	/// The callback is called for every page emitted after this point,
+	/// with information about the page number and the total number of pages in the document.
+	/// Use [`Page::header_area`] and [`Page::footer_area`] to draw in the page margins.
+	///
+	/// Since the total number of pages is only known at the end,
+	/// pages are kept in memory and written when the document is finished with [`PdfWriter::finish`].
+	/// If the PDF writer is dropped without calling [`PdfWriter::finish`], the pages are still written,
+	/// but any error that occurs while doing so is lost.
+	pub fn set_header_footer(&mut self, callback: impl FnMut(&mut Page, PageInfo) -> Result<(), Error> + 'static) {
+		self.header_footer = Some(Box::new(callback));
+	}
+
+	/// Write all pending pages, drawing the header and footer on each of them.
+	pub(crate) fn flush_pending_pages(&mut self) -> Result<(), Error> {
+		let pages = self.pending_pages.take();
+		let first = self.emitted_pages.get();
+		let count = first + pages.len();
+		for (i, mut page) in pages.into_iter().enumerate() {
+			if let Some(callback) = &mut self.header_footer {
+				callback(&mut page, PageInfo { index: first + i, count })?;
+			}
+			page.write(self)?;
+		}
+		Ok(())
+	}
+}
+
+impl<W> Drop for PdfWriter<W> {
+	fn drop(&mut self) {
+		// Don't run the header and footer callback while unwinding, it could panic again.
+		if !std::thread::panicking() {
+			let _ = self.flush_pending_pages();
+		}
+	}
+}
+
+impl Page {
+	/// Get the area of the top margin, between the left and right margin.
+	pub fn header_area(&self) -> Box2<Mm> {
+		Box2::new(
+			Point2::new(self.margins.left.get(), 0.0),
+			Point2::new(self.size.width - self.margins.right.get(), self.margins.top.get()),
+		)
+	}
+
+	/// Get the area of the bottom margin, between the left and right margin.
+	pub fn footer_area(&self) -> Box2<Mm> {
+		Box2::new(
+			Point2::new(self.margins.left.get(), self.size.height - self.margins.bottom.get()),
+			Point2::new(self.size.width - self.margins.right.get(), self.size.height),
+		)
+	}
+
+	/// Copy the page contents into a new page that can be kept until the document is finished.
+	pub(crate) fn snapshot(&self) -> Result<Page, Error> {
+		let size_pt = self.size * PT_PER_MM;
+		let extents = cairo::Rectangle {
+			x: 0.0,
+			y: 0.0,
+			width: size_pt.width,
+			height: size_pt.height,
+		};
+		let recording = cairo::RecordingSurface::create(cairo::Content::ColorAlpha, extents)
+			.map_err(Error::CreateSurface)?;
+		let cairo = cairo::Context::new(&recording);
+		cairo.set_source_surface(&self.cairo.get_target(), 0.0, 0.0);
+		cairo.paint();
+
+		Ok(Page {
+			cairo,
+			fonts: self.fonts.clone(),
+			size: self.size,
+			margins: self.margins,
+			cursor_y: self.margins.top,
+			destinations: self.destinations.clone(),
+			links: self.links.clone(),
+		})
+	}
+}
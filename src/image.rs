@@ -0,0 +1,242 @@
+use crate::{
+	Box2,
+	BoxPosition,
+	Error,
+	Length,
+	Mm,
+	Page,
+	PT_PER_MM,
+	Size2,
+};
+
+/// A raster image that can be drawn on a page.
+///
+/// PNG images are decoded when they are loaded.
+/// JPEG images are embedded in the PDF as-is, without decoding and re-encoding them.
+pub struct Image {
+	surface: cairo::ImageSurface,
+}
+
+/// How an image is scaled to fit a target area.
+#[derive(Debug, Copy, Clone, Default)]
+pub enum ImageFit {
+	/// Scale the image to fit entirely inside the area, preserving the aspect ratio.
+	#[default]
+	Contain,
+
+	/// Scale the image to cover the whole area, preserving the aspect ratio.
+	///
+	/// Parts of the image that fall outside of the area are clipped.
+	Cover,
+
+	/// Scale the image to exactly fill the area, ignoring the aspect ratio.
+	Stretch,
+}
+
+impl Image {
+	/// Load a PNG or JPEG image from memory.
+	///
+	/// The image format is detected from the data.
+	pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+		if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+			Self::from_png(data)
+		} else if data.starts_with(b"\xFF\xD8") {
+			Self::from_jpeg(data)
+		} else {
+			Err(Error::InvalidImage("unrecognized image format"))
+		}
+	}
+
+	/// Load a PNG or JPEG image from a file.
+	///
+	/// The image format is detected from the file contents.
+	pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+		Self::from_bytes(&std::fs::read(path)?)
+	}
+
+	/// Load a PNG image from memory.
+	pub fn from_png(data: &[u8]) -> Result<Self, Error> {
+		let surface = cairo::ImageSurface::create_from_png(&mut std::io::Cursor::new(data))
+			.map_err(Error::DecodePng)?;
+		Ok(Self { surface })
+	}
+
+	/// Load a JPEG image from memory.
+	///
+	/// Only the image header is parsed.
+	/// The data is embedded in the PDF without re-encoding it.
+	pub fn from_jpeg(data: &[u8]) -> Result<Self, Error> {
+		let (width, height) = jpeg_dimensions(data)
+			.ok_or(Error::InvalidImage("invalid JPEG header"))?;
+		let surface = cairo::ImageSurface::create(cairo::Format::Rgb24, width.into(), height.into())
+			.map_err(Error::CreateSurface)?;
+		surface.set_mime_data(cairo::MIME_TYPE_JPEG, Vec::from(data))
+			.map_err(Error::CreateSurface)?;
+		Ok(Self { surface })
+	}
+
+	/// Get the width of the image in pixels.
+	pub fn width(&self) -> u32 {
+		self.surface.get_width() as u32
+	}
+
+	/// Get the height of the image in pixels.
+	pub fn height(&self) -> u32 {
+		self.surface.get_height() as u32
+	}
+
+	/// Get the aspect ratio of the image (width divided by height).
+	pub fn aspect_ratio(&self) -> f64 {
+		f64::from(self.width()) / f64::from(self.height())
+	}
+
+	/// Get the size of the image when scaled to the given width, preserving the aspect ratio.
+	pub fn size_for_width(&self, width: Length<Mm>) -> Size2<Mm> {
+		Size2::new(width.get(), width.get() / self.aspect_ratio())
+	}
+
+	/// Get the size of the image when scaled to the given height, preserving the aspect ratio.
+	pub fn size_for_height(&self, height: Length<Mm>) -> Size2<Mm> {
+		Size2::new(height.get() * self.aspect_ratio(), height.get())
+	}
+}
+
+impl Page {
+	/// Draw an image into a target area.
+	///
+	/// The image is scaled to the area according to the fit mode and centered in the area.
+	pub fn draw_image(&self, image: &Image, area: Box2<Mm>, fit: ImageFit) {
+		let area = area * PT_PER_MM;
+		let image_width = f64::from(image.width());
+		let image_height = f64::from(image.height());
+		let scale_x = area.width() / image_width;
+		let scale_y = area.height() / image_height;
+		let (scale_x, scale_y) = match fit {
+			ImageFit::Contain => (scale_x.min(scale_y), scale_x.min(scale_y)),
+			ImageFit::Cover => (scale_x.max(scale_y), scale_x.max(scale_y)),
+			ImageFit::Stretch => (scale_x, scale_y),
+		};
+
+		let x = area.min.x + (area.width() - image_width * scale_x) * 0.5;
+		let y = area.min.y + (area.height() - image_height * scale_y) * 0.5;
+
+		self.cairo.save();
+		self.cairo.rectangle(area.min.x, area.min.y, area.width(), area.height());
+		self.cairo.clip();
+		self.cairo.translate(x, y);
+		self.cairo.scale(scale_x, scale_y);
+		self.cairo.set_source_surface(&image.surface, 0.0, 0.0);
+		self.cairo.paint();
+		self.cairo.restore();
+	}
+
+	/// Draw an image with the given size at a position.
+	///
+	/// The image is anchored to the position the same way as a text box.
+	/// Use [`Image::size_for_width`] or [`Image::size_for_height`] to preserve the aspect ratio of the image.
+	pub fn draw_image_at(&self, image: &Image, position: &BoxPosition, size: Size2<Mm>, fit: ImageFit) -> Box2<Mm> {
+		let origin = position.point + position.alignment_offset(size, Length::new(size.height));
+		let area = Box2::new(origin, origin + size);
+		self.draw_image(image, area, fit);
+		area
+	}
+}
+
+/// Get the width and height of a JPEG image from the frame header.
+fn jpeg_dimensions(data: &[u8]) -> Option<(u16, u16)> {
+	if !data.starts_with(b"\xFF\xD8") {
+		return None;
+	}
+
+	let mut i = 2;
+	loop {
+		// Skip to the next marker, allowing any number of fill bytes.
+		while *data.get(i)? != 0xFF {
+			i += 1;
+		}
+		while *data.get(i)? == 0xFF {
+			i += 1;
+		}
+		let marker = *data.get(i)?;
+		i += 1;
+
+		// Markers without a segment.
+		if marker == 0x01 || (0xD0..=0xD9).contains(&marker) {
+			continue;
+		}
+
+		let length = usize::from(u16::from_be_bytes([*data.get(i)?, *data.get(i + 1)?]));
+
+		// Start of frame markers, except DHT, JPG and DAC which share the range.
+		if (0xC0..=0xCF).contains(&marker) && marker != 0xC4 && marker != 0xC8 && marker != 0xCC {
+			let height = u16::from_be_bytes([*data.get(i + 3)?, *data.get(i + 4)?]);
+			let width = u16::from_be_bytes([*data.get(i + 5)?, *data.get(i + 6)?]);
+			if width == 0 || height == 0 {
+				return None;
+			}
+			return Some((width, height));
+		}
+
+		i += length;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::jpeg_dimensions;
+
+	/// Build a minimal JPEG with an APP0 segment and a frame header with the given marker.
+	fn jpeg(sof: u8, width: u16, height: u16) -> Vec<u8> {
+		let mut data = vec![0xFF, 0xD8];
+		data.extend_from_slice(&[0xFF, 0xE0, 0x00, 0x10, b'J', b'F', b'I', b'F', 0x00, 0x01, 0x01, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00]);
+		data.extend_from_slice(&[0xFF, sof, 0x00, 0x11, 0x08]);
+		data.extend_from_slice(&height.to_be_bytes());
+		data.extend_from_slice(&width.to_be_bytes());
+		data.extend_from_slice(&[0x03, 0x01, 0x22, 0x00, 0x02, 0x11, 0x01, 0x03, 0x11, 0x01]);
+		data.extend_from_slice(&[0xFF, 0xD9]);
+		data
+	}
+
+	#[test]
+	fn baseline_frame() {
+		assert_eq!(jpeg_dimensions(&jpeg(0xC0, 640, 480)), Some((640, 480)));
+	}
+
+	#[test]
+	fn progressive_frame() {
+		assert_eq!(jpeg_dimensions(&jpeg(0xC2, 300, 1200)), Some((300, 1200)));
+	}
+
+	#[test]
+	fn fill_bytes_before_marker() {
+		let mut data = jpeg(0xC0, 16, 9);
+		data.insert(2, 0xFF);
+		data.insert(2, 0xFF);
+		assert_eq!(jpeg_dimensions(&data), Some((16, 9)));
+	}
+
+	#[test]
+	fn huffman_table_is_not_a_frame() {
+		let mut data = vec![0xFF, 0xD8, 0xFF, 0xC4, 0x00, 0x07, 0x00, 0x10, 0x00, 0x20, 0x00];
+		data.extend_from_slice(&jpeg(0xC0, 10, 20)[2..]);
+		assert_eq!(jpeg_dimensions(&data), Some((10, 20)));
+	}
+
+	#[test]
+	fn truncated() {
+		let data = jpeg(0xC0, 640, 480);
+		// Every prefix that ends before the width of the frame header is incomplete.
+		let frame_end = 2 + 18 + 9;
+		for len in 0..frame_end {
+			assert_eq!(jpeg_dimensions(&data[..len]), None, "prefix of length {}", len);
+		}
+		assert_eq!(jpeg_dimensions(&data[..frame_end]), Some((640, 480)));
+	}
+
+	#[test]
+	fn no_frame() {
+		assert_eq!(jpeg_dimensions(&[0xFF, 0xD8, 0xFF, 0xD9]), None);
+		assert_eq!(jpeg_dimensions(b"\x89PNG\r\n\x1a\n"), None);
+		assert_eq!(jpeg_dimensions(&jpeg(0xC0, 0, 480)), None);
+	}
+}
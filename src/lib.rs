@@ -2,9 +2,45 @@ pub const A4: Size2<Mm> = Size2::new(210.0, 297.0);
 
 pub use euclid;
 
+mod error;
+pub use error::*;
+
+mod color;
+pub use color::*;
+
+mod drawing;
+pub use drawing::*;
+
 mod font_spec;
 pub use font_spec::*;
 
+mod fonts;
+pub use fonts::{FontFace, FontResolution, FontSubstitution};
+use fonts::{FontRegistry, Fonts};
+
+mod header_footer;
+pub use header_footer::PageInfo;
+use header_footer::HeaderFooterFn;
+
+mod image;
+pub use image::*;
+
+mod link;
+pub use link::LinkTarget;
+use link::{Destination, Link};
+
+mod metadata;
+pub use metadata::*;
+
+mod outline;
+pub use outline::*;
+
+mod rich_text;
+pub use rich_text::*;
+
+mod svg;
+pub use svg::Svg;
+
 mod text_style;
 pub use text_style::*;
 
@@ -14,64 +50,134 @@ pub use units::*;
 mod table;
 pub use table::*;
 
-pub struct PdfWriter {
+mod table_border;
+pub use table_border::*;
+
+pub struct PdfWriter<W> {
 	surface: cairo::PdfSurface,
 	cairo: cairo::Context,
+	fonts: Fonts,
+	stream: std::marker::PhantomData<W>,
+	outline_count: usize,
+	header_footer: Option<HeaderFooterFn>,
+	pending_pages: std::cell::RefCell<Vec<Page>>,
+	emitted_pages: std::cell::Cell<usize>,
 }
 
 pub struct Page {
 	cairo: cairo::Context,
+	fonts: Fonts,
 	size: Size2<Mm>,
 	margins: Margins<Mm>,
 	cursor_y: Length<Mm>,
+	destinations: Vec<Destination>,
+	links: Vec<Link>,
 }
 
-impl PdfWriter {
-	pub fn new<W: std::io::Write + 'static>(stream: W) -> Result<Self, String> {
+impl<W: std::io::Write + 'static> PdfWriter<W> {
+	pub fn new(stream: W) -> Result<Self, Error> {
 		let surface = cairo::PdfSurface::for_stream(
 			100.0,
 			100.0,
 			stream
 		);
-		let surface = surface.map_err(|e| format!("failed to create PDF surface: {}", e))?;
+		let surface = surface.map_err(Error::CreateSurface)?;
 		let cairo = cairo::Context::new(&surface);
-		let pango = pango::Context::new();
-		let font_map = pangocairo::FontMap::get_default()
-			.ok_or_else(|| "failed to get default font map")?;
-		pango.set_font_map(&font_map);
+		let fonts = FontRegistry::new()?;
+		Metadata::default().apply_to_surface(&surface)?;
 
 		Ok(Self {
 			surface,
 			cairo,
+			fonts,
+			stream: std::marker::PhantomData,
+			outline_count: 0,
+			header_footer: None,
+			pending_pages: std::cell::RefCell::new(Vec::new()),
+			emitted_pages: std::cell::Cell::new(0),
 		})
 	}
 
-	pub fn text_box(
+	/// Finish the PDF document and get back the output stream.
+	///
+	/// This writes all pending pages and data to the stream and flushes it.
+	/// Any error that occurred while writing to the stream is reported here.
+	pub fn finish(mut self) -> Result<W, Error> {
+		self.flush_pending_pages()?;
+		let stream = self.surface.finish_output_stream()
+			.map_err(|e| Error::Io(e.error))?;
+		let mut stream = stream.downcast::<W>()
+			.unwrap_or_else(|_| unreachable!("PDF surface has an output stream of the wrong type"));
+		stream.flush()?;
+		Ok(*stream)
+	}
+}
+
+impl<W> PdfWriter<W> {
+	/// Set the document metadata.
+	///
+	/// Fields that are `None` are left unchanged.
+	pub fn set_metadata(&mut self, metadata: &Metadata) -> Result<(), Error> {
+		metadata.apply_to_surface(&self.surface)
+	}
+
+	/// Add an entry to the document outline.
+	///
+	/// The entry points to the vertical position `y` on the given page.
+	/// The page must be emitted after adding the entry,
+	/// since the target position is recorded as part of the page.
+	///
+	/// Use [`OutlineId::ROOT`] as parent to add a top-level entry.
+	/// The returned ID can be used as parent for nested entries.
+	pub fn add_outline(
+		&mut self,
+		parent: OutlineId,
+		title: &str,
+		page: &mut Page,
+		y: Length<Mm>,
+		flags: OutlineFlags,
+	) -> Result<OutlineId, Error> {
+		let name = format!("pdf-writer-outline-{}", self.outline_count);
+		self.outline_count += 1;
+		page.add_destination_at(name.clone(), Point2::new(page.margins.left.get(), y.get()));
+
+		let link = format!("dest={}", link::quote_attribute(&name));
+		let id = self.surface.add_outline(parent.0, title, &link, flags.to_cairo())
+			.map_err(Error::AddOutline)?;
+		Ok(OutlineId(id))
+	}
+
+	pub fn text_box<'a>(
 		&self,
-		text: &str,
+		text: impl Into<Text<'a>>,
 		style: &TextStyle,
 		position: BoxPosition,
 		width: Option<Length<Mm>>,
-	) -> Result<TextBox, String> {
-		TextBox::new(&self.cairo, text, style, position, width)
+	) -> Result<TextBox, Error> {
+		TextBox::new(&self.cairo, &self.fonts, text.into(), style, position, width)
 	}
 
-	pub fn page(&mut self, size: Size2<Mm>, margins: Margins<Mm>) -> Result<Page, String> {
-		let device_size = size * PT_PER_MM * PANGO_PER_PT;
-		let width = device_size.width.round() as i32;
-		let height = device_size.height.round() as i32;
-
-		let buffer = self.cairo
-			.get_target()
-			.create_similar(cairo::Content::Alpha, width, height)
-			.map_err(|e| format!("failed to create buffer surface for page: {}", e))?;
-		let cairo = cairo::Context::new(&buffer);
+	pub fn page(&mut self, size: Size2<Mm>, margins: Margins<Mm>) -> Result<Page, Error> {
+		// Record all drawing operations, so they can be replayed on the PDF surface as vector graphics.
+		let size_pt = size * PT_PER_MM;
+		let extents = cairo::Rectangle {
+			x: 0.0,
+			y: 0.0,
+			width: size_pt.width,
+			height: size_pt.height,
+		};
+		let recording = cairo::RecordingSurface::create(cairo::Content::ColorAlpha, extents)
+			.map_err(Error::CreateSurface)?;
+		let cairo = cairo::Context::new(&recording);
 		let cursor_y = margins.top;
 		Ok(Page {
 			cairo,
+			fonts: self.fonts.clone(),
 			size,
 			margins,
 			cursor_y,
+			destinations: Vec::new(),
+			links: Vec::new(),
 		})
 	}
 }
@@ -109,71 +215,186 @@ impl Page {
 		)
 	}
 
-	pub fn write_text(&mut self, text: &str, style: &TextStyle) -> Result<(), String> {
+	/// Write text at the cursor and advance the cursor past the text.
+	///
+	/// The text is wrapped to the text width of the page.
+	/// If the text does not fit above the bottom margin, it is split at a line boundary.
+	/// The current page is then emitted to the PDF writer and the remaining lines continue on a fresh page.
+	pub fn write_text<'a, W>(&mut self, pdf: &PdfWriter<W>, text: impl Into<Text<'a>>, style: &TextStyle) -> Result<(), Error> {
 		let position = BoxPosition::at_xy(self.margins.left, self.cursor_y);
-		let extents = self.draw_text_box(text, style, position, Some(self.text_width()))?;
+		let text_box = TextBox::new(&self.cairo, &self.fonts, text.into(), style, position, Some(self.text_width()))?;
+		let mut lines = text_box.layout.get_iter()
+			.ok_or(Error::CreateLayout)?;
+
+		// Vertical offset within the layout of the first line drawn on the current page.
+		let mut page_offset = mm(0.0);
+		loop {
+			let (_, logical) = lines.get_line_extents();
+			let (top, bottom) = lines.get_line_yrange();
+			let top = mm_from_pango(top);
+			let bottom = mm_from_pango(bottom);
+
+			// Move to a new page if the line doesn't fit, unless it is already at the top of the page.
+			let line_top = self.cursor_y + top - page_offset;
+			let line_bottom = self.cursor_y + bottom - page_offset;
+			if line_bottom > self.text_bottom() && line_top > self.margins.top {
+				self.next_page(pdf)?;
+				page_offset = top;
+			}
+
+			let line = lines.get_line_readonly()
+				.ok_or(Error::CreateLayout)?;
+			let x = self.margins.left + mm_from_pango(logical.x);
+			let y = self.cursor_y + mm_from_pango(lines.get_baseline()) - page_offset;
+			self.cairo.save();
+			text_box.color.set_as_source(&self.cairo);
+			self.cairo.move_to((x * PT_PER_MM).get(), (y * PT_PER_MM).get());
+			pangocairo::show_layout_line(&self.cairo, &line);
+			self.cairo.restore();
+
+			if !lines.next_line() {
+				break;
+			}
+		}
 
-		self.cursor_y += Length::new(extents.logical.height());
+		self.cursor_y += text_box.logical_height() - page_offset;
 		Ok(())
 	}
 
-	pub fn draw_text_box(
+	/// Emit the page and continue on a fresh page with the same size and margins.
+	///
+	/// The page contents are cleared and the cursor is moved back to the top margin.
+	pub fn next_page<W>(&mut self, pdf: &PdfWriter<W>) -> Result<(), Error> {
+		self.emit(pdf)?;
+		self.clear();
+		self.cursor_y = self.margins.top;
+		Ok(())
+	}
+
+	/// Get the vertical position of the bottom margin.
+	fn text_bottom(&self) -> Length<Mm> {
+		Length::<Mm>::new(self.size.height) - self.margins.bottom
+	}
+
+	pub fn draw_text_box<'a>(
 		&self,
-		text: &str,
+		text: impl Into<Text<'a>>,
 		style: &TextStyle,
 		position: BoxPosition,
 		width: Option<Length<Mm>>,
-	) -> Result<TextExtent, String> {
-		let extents = TextBox::new(&self.cairo, text, style, position, width)?.draw(self);
+	) -> Result<TextExtent, Error> {
+		let extents = TextBox::new(&self.cairo, &self.fonts, text.into(), style, position, width)?.draw(self);
 		Ok(extents)
 	}
 
+	/// Add a named destination at the cursor.
+	///
+	/// The destination can be used as target for links with [`LinkTarget::Destination`].
+	/// Destination names must be unique within the document.
+	pub fn add_destination(&mut self, name: impl Into<String>) {
+		let position = self.cursor();
+		self.add_destination_at(name, position);
+	}
+
+	/// Add a named destination at a specific position on the page.
+	///
+	/// The destination can be used as target for links with [`LinkTarget::Destination`].
+	/// Destination names must be unique within the document.
+	pub fn add_destination_at(&mut self, name: impl Into<String>, position: Point2<Mm>) {
+		self.destinations.push(Destination {
+			name: name.into(),
+			position,
+		});
+	}
+
+	/// Make a region of the page a clickable link.
+	///
+	/// The region can be any area, such as the logical extent of a drawn text box.
+	pub fn add_link(&mut self, area: Box2<Mm>, target: LinkTarget) {
+		self.links.push(Link { area, target });
+	}
+
 	/// Emit the page.
-	pub fn emit(&self, pdf: &PdfWriter) -> Result<(), String> {
+	///
+	/// If a header and footer callback is set on the PDF writer,
+	/// the page is kept in memory until the document is finished.
+	pub fn emit<W>(&self, pdf: &PdfWriter<W>) -> Result<(), Error> {
+		if pdf.header_footer.is_some() {
+			pdf.pending_pages.borrow_mut().push(self.snapshot()?);
+			Ok(())
+		} else {
+			self.write(pdf)
+		}
+	}
+
+	/// Write the page to the PDF surface.
+	fn write<W>(&self, pdf: &PdfWriter<W>) -> Result<(), Error> {
 		let size_pt = self.size * PT_PER_MM;
 		pdf.cairo.save();
 		pdf.surface.set_size(size_pt.width, size_pt.height)
-			.map_err(|e| format!("failed to set page size: {}", e))?;
+			.map_err(Error::SetPageSize)?;
 		pdf.cairo.set_source_surface(&self.cairo.get_target(), 0.0, 0.0);
 		pdf.cairo.rectangle(0.0, 0.0, size_pt.width, size_pt.height);
 		pdf.cairo.fill();
 		pdf.cairo.restore();
+
+		for destination in &self.destinations {
+			destination.emit(&pdf.cairo);
+		}
+		for link in &self.links {
+			link.emit(&pdf.cairo);
+		}
 		pdf.cairo.show_page();
+		pdf.emitted_pages.set(pdf.emitted_pages.get() + 1);
 		Ok(())
 	}
 
 	/// Clear the page contents.
 	pub fn clear(&mut self) {
-		let size_pt = self.size * PT_PER_MM;
+		// An unclipped clear makes the recording surface drop all recorded operations.
 		self.cairo.save();
+		self.cairo.reset_clip();
 		self.cairo.set_operator(cairo::Operator::Clear);
-		self.cairo.rectangle(0.0, 0.0, size_pt.width, size_pt.height);
-		self.cairo.paint_with_alpha(1.0);
+		self.cairo.paint();
 		self.cairo.restore();
+		self.destinations.clear();
+		self.links.clear();
 	}
 }
 
 /// A text box that can be rendered to a page.
 pub struct TextBox {
 	layout: pango::Layout,
+	fonts: Fonts,
+	attributes: Option<pango::AttrList>,
+	color: Color,
 	position: BoxPosition,
 }
 
 impl TextBox {
-	fn new(cairo: &cairo::Context, text: &str, style: &TextStyle, position: BoxPosition, width: Option<Length<Mm>>) -> Result<Self, String> {
-		let layout = pangocairo::create_layout(cairo)
-			.ok_or("failed to create pango layout")?;
-		load_font(&layout, &style.font)?;
-		style.apply_to_layout(&layout);
+	fn new(cairo: &cairo::Context, fonts: &Fonts, text: Text, style: &TextStyle, position: BoxPosition, width: Option<Length<Mm>>) -> Result<Self, Error> {
+		let layout = fonts.borrow().create_layout(cairo)?;
+		let font = load_font(&layout, fonts, &style.font)?;
+		if let Text::Rich(rich) = text {
+			for family in rich.families() {
+				let mut description = font.clone();
+				description.set_family(&fonts.borrow().resolve_families(Some(family))?);
+				check_font(&layout, fonts, &description, family)?;
+			}
+		}
+		let (text, attributes) = text.to_pango(&fonts.borrow())?;
+		layout.set_text(&text);
+		style.apply_to_layout(&layout, &font, attributes.as_ref())?;
 
 		if let Some(width) = width {
 			layout.set_width(((width * PT_PER_MM * PANGO_PER_PT).get()).round() as i32);
 		}
 
-		layout.set_text(text);
-
 		Ok(Self {
 			layout,
+			fonts: fonts.clone(),
+			attributes,
+			color: style.color,
 			position,
 		})
 	}
@@ -224,9 +445,10 @@ impl TextBox {
 	}
 
 	/// Set the style of the text box.
-	pub fn set_style(&mut self, style: &TextStyle) -> Result<(), String> {
-		load_font(&self.layout, &style.font)?;
-		style.apply_to_layout(&self.layout);
+	pub fn set_style(&mut self, style: &TextStyle) -> Result<(), Error> {
+		let font = load_font(&self.layout, &self.fonts, &style.font)?;
+		style.apply_to_layout(&self.layout, &font, self.attributes.as_ref())?;
+		self.color = style.color;
 		Ok(())
 	}
 
@@ -251,6 +473,31 @@ impl TextBox {
 		}
 	}
 
+	/// Get the font faces used to render the glyphs of the text box.
+	///
+	/// This includes fallback fonts that were used for characters missing from the requested font.
+	/// Each face is reported once, in the order it is first used.
+	pub fn fonts_used(&self) -> Vec<FontFace> {
+		let mut faces = Vec::new();
+		let mut iter = match self.layout.get_iter() {
+			Some(x) => x,
+			None => return faces,
+		};
+		loop {
+			let face = iter.get_run_readonly()
+				.and_then(|run| FontFace::from_pango(&run.item().analysis().font()));
+			if let Some(face) = face {
+				if !faces.contains(&face) {
+					faces.push(face);
+				}
+			}
+			if !iter.next_run() {
+				break;
+			}
+		}
+		faces
+	}
+
 	/// Draw the text on a page.
 	pub fn draw(&self, page: &Page) -> TextExtent {
 		self.draw_offset(page, Vector2::new(0.0, 0.0))
@@ -263,16 +510,31 @@ impl TextBox {
 		extents.absolute = extents.absolute.translate(offset);
 
 		let position = extents.logical.min * PT_PER_MM;
+		page.cairo.save();
+		self.color.set_as_source(&page.cairo);
 		page.cairo.move_to(position.x, position.y);
 		pangocairo::show_layout(&page.cairo, &self.layout);
+		page.cairo.restore();
 		extents
 	}
 }
 
-fn load_font(layout: &pango::Layout, font: &FontSpec) -> Result<(), String> {
+fn mm_from_pango(value: i32) -> Length<Mm> {
+	Length::<PangoUnit>::new(value.into()) * PT_PER_PANGO * MM_PER_PT
+}
+
+/// Load a font, returning the font description with the family resolved.
+fn load_font(layout: &pango::Layout, fonts: &Fonts, font: &FontSpec) -> Result<pango::FontDescription, Error> {
+	let description = fonts.borrow().describe(font)?;
+	check_font(layout, fonts, &description, &font.family)?;
+	Ok(description)
+}
+
+/// Load the font for a description and check it against the font resolution mode.
+fn check_font(layout: &pango::Layout, fonts: &Fonts, description: &pango::FontDescription, family: &str) -> Result<(), Error> {
 	let pango = layout.get_context()
-		.ok_or("failed to get pango context for text layout")?;
-	pango.load_font(&font.to_pango())
-		.ok_or("failed to load font")?;
-	Ok(())
+		.ok_or(Error::CreateLayout)?;
+	let loaded = pango.load_font(description)
+		.ok_or_else(|| Error::LoadFont { family: family.to_string() })?;
+	fonts.borrow_mut().check_loaded(description, &loaded)
 }
@@ -0,0 +1,112 @@
+use crate::{Box2, Mm, Point2, PT_PER_MM};
+
+/// The target of a link.
+#[derive(Debug, Clone)]
+pub enum LinkTarget {
+	/// An external URI, such as a website.
+	Uri(String),
+
+	/// A named destination within the document.
+	Destination(String),
+}
+
+impl LinkTarget {
+	/// Create a link target for an external URI.
+	pub fn uri(uri: impl Into<String>) -> Self {
+		Self::Uri(uri.into())
+	}
+
+	/// Create a link target for a named destination within the document.
+	pub fn destination(name: impl Into<String>) -> Self {
+		Self::Destination(name.into())
+	}
+}
+
+/// A clickable region on a page.
+#[derive(Clone)]
+pub(crate) struct Link {
+	pub(crate) area: Box2<Mm>,
+	pub(crate) target: LinkTarget,
+}
+
+impl Link {
+	/// Emit the link as tag on a cairo context.
+	pub(crate) fn emit(&self, cairo: &cairo::Context) {
+		let area = self.area * PT_PER_MM;
+		let target = match &self.target {
+			LinkTarget::Uri(uri) => format!("uri={}", quote_attribute(uri)),
+			LinkTarget::Destination(name) => format!("dest={}", quote_attribute(name)),
+		};
+		let attributes = format!(
+			"rect=[{} {} {} {}] {}",
+			area.min.x,
+			area.min.y,
+			area.width(),
+			area.height(),
+			target,
+		);
+		cairo.tag_begin("Link", &attributes);
+		cairo.tag_end("Link");
+	}
+}
+
+/// A named destination on a page that can be the target of links.
+#[derive(Clone)]
+pub(crate) struct Destination {
+	pub(crate) name: String,
+	pub(crate) position: Point2<Mm>,
+}
+
+impl Destination {
+	/// Emit the destination as tag on a cairo context.
+	pub(crate) fn emit(&self, cairo: &cairo::Context) {
+		let position = self.position * PT_PER_MM;
+		let attributes = format!(
+			"name={} x={} y={}",
+			quote_attribute(&self.name),
+			position.x,
+			position.y,
+		);
+		cairo.tag_begin("cairo.dest", &attributes);
+		cairo.tag_end("cairo.dest");
+	}
+}
+
+/// Quote a string for use as value in cairo tag attributes.
+pub(crate) fn quote_attribute(value: &str) -> String {
+	let mut quoted = String::with_capacity(value.len() + 2);
+	quoted.push('\'');
+	for c in value.chars() {
+		if c == '\'' || c == '\\' {
+			quoted.push('\\');
+		}
+		quoted.push(c);
+	}
+	quoted.push('\'');
+	quoted
+}
+
+#[cfg(test)]
+mod test {
+	use super::quote_attribute;
+
+	#[test]
+	fn quote_plain() {
+		assert_eq!(quote_attribute("https://example.com"), "'https://example.com'");
+		assert_eq!(quote_attribute(""), "''");
+	}
+
+	#[test]
+	fn quote_quotes() {
+		assert_eq!(quote_attribute("it's"), r"'it\'s'");
+		assert_eq!(quote_attribute("'"), r"'\''");
+		assert_eq!(quote_attribute("\"double\""), "'\"double\"'");
+	}
+
+	#[test]
+	fn quote_backslashes() {
+		assert_eq!(quote_attribute(r"a\b"), r"'a\\b'");
+		assert_eq!(quote_attribute(r"\'"), r"'\\\''");
+		assert_eq!(quote_attribute(r"trailing\"), r"'trailing\\'");
+	}
+}
@@ -0,0 +1,116 @@
+use crate::Error;
+
+/// Document metadata for a PDF file.
+///
+/// Dates should be formatted according to ISO 8601,
+/// for example `2021-03-21T14:30:00+01:00`.
+#[derive(Debug, Clone)]
+pub struct Metadata {
+	pub title: Option<String>,
+	pub author: Option<String>,
+	pub subject: Option<String>,
+	pub keywords: Option<String>,
+	pub creator: Option<String>,
+	pub create_date: Option<String>,
+	pub mod_date: Option<String>,
+}
+
+impl Metadata {
+	/// Create new metadata with the default values.
+	///
+	/// The creator is set to `pdf-writer`, all other fields are empty.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the document title.
+	pub fn title(self, title: impl Into<String>) -> Self {
+		Self {
+			title: Some(title.into()),
+			..self
+		}
+	}
+
+	/// Set the document author.
+	pub fn author(self, author: impl Into<String>) -> Self {
+		Self {
+			author: Some(author.into()),
+			..self
+		}
+	}
+
+	/// Set the document subject.
+	pub fn subject(self, subject: impl Into<String>) -> Self {
+		Self {
+			subject: Some(subject.into()),
+			..self
+		}
+	}
+
+	/// Set the document keywords.
+	///
+	/// Multiple keywords should be separated by commas.
+	pub fn keywords(self, keywords: impl Into<String>) -> Self {
+		Self {
+			keywords: Some(keywords.into()),
+			..self
+		}
+	}
+
+	/// Set the name of the application that created the document.
+	pub fn creator(self, creator: impl Into<String>) -> Self {
+		Self {
+			creator: Some(creator.into()),
+			..self
+		}
+	}
+
+	/// Set the creation date of the document.
+	pub fn create_date(self, date: impl Into<String>) -> Self {
+		Self {
+			create_date: Some(date.into()),
+			..self
+		}
+	}
+
+	/// Set the modification date of the document.
+	pub fn mod_date(self, date: impl Into<String>) -> Self {
+		Self {
+			mod_date: Some(date.into()),
+			..self
+		}
+	}
+
+	pub(crate) fn apply_to_surface(&self, surface: &cairo::PdfSurface) -> Result<(), Error> {
+		let fields = [
+			(cairo::PdfMetadata::Title, &self.title),
+			(cairo::PdfMetadata::Author, &self.author),
+			(cairo::PdfMetadata::Subject, &self.subject),
+			(cairo::PdfMetadata::Keywords, &self.keywords),
+			(cairo::PdfMetadata::Creator, &self.creator),
+			(cairo::PdfMetadata::CreateDate, &self.create_date),
+			(cairo::PdfMetadata::ModDate, &self.mod_date),
+		];
+
+		for (key, value) in &fields {
+			if let Some(value) = value {
+				surface.set_metadata(*key, value).map_err(Error::SetMetadata)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+impl std::default::Default for Metadata {
+	fn default() -> Self {
+		Self {
+			title: None,
+			author: None,
+			subject: None,
+			keywords: None,
+			creator: Some(String::from("pdf-writer")),
+			create_date: None,
+			mod_date: None,
+		}
+	}
+}
@@ -0,0 +1,62 @@
+/// Identifier for an entry in the document outline.
+///
+/// Use [`OutlineId::ROOT`] to add top-level entries.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct OutlineId(pub(crate) i32);
+
+impl OutlineId {
+	/// The root of the outline tree.
+	pub const ROOT: Self = Self(0);
+}
+
+/// Display flags for an outline entry.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct OutlineFlags {
+	/// Show the children of the entry initially.
+	pub open: bool,
+
+	/// Show the entry in bold.
+	pub bold: bool,
+
+	/// Show the entry in italic.
+	pub italic: bool,
+}
+
+impl OutlineFlags {
+	/// Create flags for an entry that is shown expanded.
+	pub fn open() -> Self {
+		Self {
+			open: true,
+			..Self::default()
+		}
+	}
+
+	/// Create flags for an entry that is shown collapsed.
+	pub fn closed() -> Self {
+		Self::default()
+	}
+
+	/// Show the entry in bold.
+	pub fn bold(self) -> Self {
+		Self {
+			bold: true,
+			..self
+		}
+	}
+
+	/// Show the entry in italic.
+	pub fn italic(self) -> Self {
+		Self {
+			italic: true,
+			..self
+		}
+	}
+
+	pub(crate) fn to_cairo(self) -> cairo::PdfOutline {
+		let mut flags = cairo::PdfOutline::empty();
+		flags.set(cairo::PdfOutline::OPEN, self.open);
+		flags.set(cairo::PdfOutline::BOLD, self.bold);
+		flags.set(cairo::PdfOutline::ITALIC, self.italic);
+		flags
+	}
+}
@@ -0,0 +1,247 @@
+use crate::{
+	Color,
+	Error,
+	FontRegistry,
+	FontStyle,
+	FontWeight,
+	Length,
+	PANGO_PER_PT,
+	Pt,
+};
+
+/// Text content for a text box.
+///
+/// Plain strings and [`RichText`] can be converted into `Text`,
+/// so functions that accept `impl Into<Text>` can be called with either.
+/// Pango markup must be wrapped explicitly with [`Text::markup`].
+#[derive(Debug, Copy, Clone)]
+pub enum Text<'a> {
+	/// Plain text, rendered entirely in the style of the text box.
+	Plain(&'a str),
+
+	/// Text made of runs that can override parts of the style of the text box.
+	Rich(&'a RichText),
+
+	/// Text formatted with pango markup, such as `<b>bold</b>` or `<span foreground="red">red</span>`.
+	///
+	/// Font families in the markup are passed to pango as-is, they are not resolved as aliases of registered fonts.
+	/// Use the family name of a registered font instead, or use [`RichText`] to refer to aliases.
+	Markup(&'a str),
+}
+
+impl<'a> From<&'a str> for Text<'a> {
+	fn from(other: &'a str) -> Self {
+		Self::Plain(other)
+	}
+}
+
+impl<'a> From<&'a String> for Text<'a> {
+	fn from(other: &'a String) -> Self {
+		Self::Plain(other)
+	}
+}
+
+impl<'a> From<&'a RichText> for Text<'a> {
+	fn from(other: &'a RichText) -> Self {
+		Self::Rich(other)
+	}
+}
+
+impl<'a> Text<'a> {
+	/// Create text from a pango markup string.
+	///
+	/// Errors in the markup are reported when the text is laid out.
+	pub fn markup(markup: &'a str) -> Self {
+		Self::Markup(markup)
+	}
+
+	/// Convert the text to a plain string with optional pango text attributes.
+	///
+	/// Font families of rich text are resolved with the font registry.
+	/// Font families in markup are passed to pango as-is.
+	pub(crate) fn to_pango(self, fonts: &FontRegistry) -> Result<(String, Option<pango::AttrList>), Error> {
+		match self {
+			Self::Plain(text) => Ok((text.to_string(), None)),
+			Self::Rich(text) => {
+				let (text, attributes) = text.to_pango(fonts)?;
+				Ok((text, Some(attributes)))
+			},
+			Self::Markup(markup) => {
+				let (attributes, text, _) = pango::parse_markup(markup, '\0')
+					.map_err(Error::ParseMarkup)?;
+				Ok((text.to_string(), Some(attributes)))
+			},
+		}
+	}
+}
+
+/// Text made of runs with their own style.
+///
+/// Each run can override parts of the style of the text box it is rendered in,
+/// for example to make a single word bold.
+#[derive(Debug, Clone, Default)]
+pub struct RichText {
+	pub runs: Vec<TextRun>,
+}
+
+/// A run of text with a partial style.
+#[derive(Debug, Clone)]
+pub struct TextRun {
+	pub text: String,
+	pub style: SpanStyle,
+}
+
+/// A partial text style for a run of text.
+///
+/// Fields that are `None` are taken from the style of the text box.
+#[derive(Debug, Clone, Default)]
+pub struct SpanStyle {
+	pub family: Option<String>,
+	pub size: Option<Length<Pt>>,
+	pub weight: Option<FontWeight>,
+	pub style: Option<FontStyle>,
+	pub color: Option<Color>,
+	pub underline: Option<bool>,
+}
+
+impl RichText {
+	/// Create a new rich text without any runs.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add a run of text with a partial style.
+	pub fn push(&mut self, text: impl Into<String>, style: SpanStyle) -> &mut Self {
+		self.runs.push(TextRun {
+			text: text.into(),
+			style,
+		});
+		self
+	}
+
+	/// Add a run of text without style overrides.
+	pub fn push_plain(&mut self, text: impl Into<String>) -> &mut Self {
+		self.push(text, SpanStyle::default())
+	}
+
+	/// Get the font families overridden by the runs.
+	pub(crate) fn families(&self) -> impl Iterator<Item = &str> {
+		self.runs.iter().filter_map(|run| run.style.family.as_deref())
+	}
+
+	/// Convert the rich text to a string with a pango attribute list.
+	fn to_pango(&self, fonts: &FontRegistry) -> Result<(String, pango::AttrList), Error> {
+		let mut text = String::new();
+		let attributes = pango::AttrList::new();
+		for run in &self.runs {
+			let start = text.len() as u32;
+			text.push_str(&run.text);
+			let end = text.len() as u32;
+			for mut attribute in run.style.to_pango(fonts)? {
+				attribute.set_start_index(start);
+				attribute.set_end_index(end);
+				attributes.insert(attribute);
+			}
+		}
+		Ok((text, attributes))
+	}
+}
+
+impl SpanStyle {
+	/// Create a new span style without any overrides.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Override the font family.
+	///
+	/// The family may be a comma separated list of fallback families.
+	pub fn family(self, family: impl Into<String>) -> Self {
+		Self {
+			family: Some(family.into()),
+			..self
+		}
+	}
+
+	/// Override the font size.
+	pub fn size(self, size: Length<Pt>) -> Self {
+		Self {
+			size: Some(size),
+			..self
+		}
+	}
+
+	/// Override the font weight.
+	pub fn weight(self, weight: FontWeight) -> Self {
+		Self {
+			weight: Some(weight),
+			..self
+		}
+	}
+
+	/// Override the font weight with [`FontWeight::Bold`].
+	pub fn bold(self) -> Self {
+		self.weight(FontWeight::Bold)
+	}
+
+	/// Override the font style.
+	pub fn style(self, style: FontStyle) -> Self {
+		Self {
+			style: Some(style),
+			..self
+		}
+	}
+
+	/// Override the font style with [`FontStyle::Italic`].
+	pub fn italic(self) -> Self {
+		self.style(FontStyle::Italic)
+	}
+
+	/// Override the text color.
+	pub fn color(self, color: Color) -> Self {
+		Self {
+			color: Some(color),
+			..self
+		}
+	}
+
+	/// Override whether the text is underlined.
+	pub fn underline(self, underline: bool) -> Self {
+		Self {
+			underline: Some(underline),
+			..self
+		}
+	}
+
+	/// Convert the span style to a list of pango attributes.
+	///
+	/// The attributes cover the whole text, the caller should set the start and end index.
+	fn to_pango(&self, fonts: &FontRegistry) -> Result<Vec<pango::Attribute>, Error> {
+		let mut attributes = Vec::new();
+		if let Some(family) = &self.family {
+			attributes.push(pango::Attribute::new_family(&fonts.resolve_families(Some(family.as_str()))?));
+		}
+		if let Some(size) = self.size {
+			attributes.push(pango::Attribute::new_size_absolute((size * PANGO_PER_PT).get().round() as i32));
+		}
+		if let Some(weight) = self.weight {
+			attributes.push(pango::Attribute::new_weight(weight.to_pango()));
+		}
+		if let Some(style) = self.style {
+			attributes.push(pango::Attribute::new_style(style.to_pango()));
+		}
+		if let Some(color) = self.color {
+			let (red, green, blue) = color.to_pango_rgb();
+			attributes.push(pango::Attribute::new_foreground(red, green, blue));
+			attributes.push(pango::Attribute::new_foreground_alpha(color.to_pango_alpha()));
+		}
+		if let Some(underline) = self.underline {
+			let underline = if underline { pango::Underline::Single } else { pango::Underline::None };
+			attributes.push(pango::Attribute::new_underline(underline));
+		}
+
+		attributes.into_iter()
+			.map(|attribute| attribute.ok_or(Error::CreateLayout))
+			.collect()
+	}
+}
@@ -0,0 +1,570 @@
+use std::collections::HashMap;
+use euclid::UnknownUnit;
+
+use crate::{
+	Box2,
+	BoxPosition,
+	Color,
+	Error,
+	Length,
+	LineCap,
+	LineJoin,
+	Mm,
+	Page,
+	PT_PER_MM,
+	Size2,
+};
+
+mod path;
+mod style;
+mod xml;
+
+use path::PathCommand;
+use style::{FillRule, Style, Stylesheet, Transform};
+
+/// Size of an SVG user unit (a CSS pixel) in millimeters.
+const MM_PER_PX: f64 = 25.4 / 96.0;
+
+/// Maximum nesting depth of `<use>` references, to protect against reference cycles.
+const MAX_USE_DEPTH: usize = 16;
+
+/// Maximum nesting depth of elements, including elements instantiated by `<use>` references.
+const MAX_NESTING: usize = 512;
+
+/// Maximum number of elements instantiated from the document, including elements instantiated by `<use>` references.
+///
+/// Nested references can expand exponentially, so a small document could otherwise exhaust memory.
+const MAX_ELEMENTS: usize = 100_000;
+
+/// An SVG image that can be drawn on a page as vector graphics.
+///
+/// The document is parsed into a list of filled and stroked paths when it is loaded.
+/// Supported are the basic shapes, paths, groups, `<use>` references, transforms
+/// and the fill and stroke properties from presentation attributes, the `style` attribute and `<style>` elements.
+/// Style sheets only support rules with a single type, class or ID selector.
+/// Text, gradients, patterns, markers, masks, clip paths and filters are not supported:
+/// text elements are skipped and gradient fills use their fallback color, if any.
+#[derive(Debug, Clone)]
+pub struct Svg {
+	shapes: Vec<Shape>,
+	view_box: Box2<UnknownUnit>,
+	width: f64,
+	height: f64,
+	aspect: AspectRatio,
+}
+
+/// A single filled and/or stroked path.
+#[derive(Debug, Clone)]
+struct Shape {
+	transform: Transform,
+	commands: Vec<PathCommand>,
+	fill: Option<(Color, FillRule)>,
+	stroke: Option<Stroke>,
+}
+
+#[derive(Debug, Clone)]
+struct Stroke {
+	color: Color,
+	width: f64,
+	cap: LineCap,
+	join: LineJoin,
+	miter_limit: f64,
+	dash: Vec<f64>,
+	dash_offset: f64,
+}
+
+/// The `preserveAspectRatio` attribute of the root element.
+#[derive(Debug, Copy, Clone)]
+struct AspectRatio {
+	/// The alignment of the view box in the viewport, or `None` to stretch it.
+	align: Option<(f64, f64)>,
+
+	/// Scale the view box to cover the viewport instead of fitting inside it.
+	slice: bool,
+}
+
+impl Svg {
+	/// Parse an SVG document.
+	///
+	/// Documents that are nested too deeply or that expand to too many elements through `<use>` references are rejected,
+	/// to protect against malicious input.
+	pub fn parse(data: &str) -> Result<Self, Error> {
+		let root = xml::parse(data).map_err(Error::InvalidSvg)?;
+		if root.name != "svg" {
+			return Err(Error::InvalidSvg(format!("expected root element `svg`, found `{}`", root.name)));
+		}
+
+		let view_box = root.attribute("viewBox")
+			.map(style::parse_number_list)
+			.and_then(|x| match x.as_slice() {
+				&[x, y, width, height] if width > 0.0 && height > 0.0 => Some(Box2::new((x, y).into(), (x + width, y + height).into())),
+				_ => None,
+			});
+
+		let reference = view_box.map(|x| x.size()).unwrap_or_else(|| Size2::new(100.0, 100.0));
+		let width = root.attribute("width").and_then(|x| style::parse_length(x, reference.width));
+		let height = root.attribute("height").and_then(|x| style::parse_length(x, reference.height));
+		let (width, height) = match (width, height, view_box) {
+			(Some(width), Some(height), _) => (width, height),
+			(Some(width), None, Some(view_box)) => (width, width * view_box.height() / view_box.width()),
+			(None, Some(height), Some(view_box)) => (height * view_box.width() / view_box.height(), height),
+			(width, height, view_box) => (
+				width.or_else(|| view_box.map(|x| x.width())).unwrap_or(300.0),
+				height.or_else(|| view_box.map(|x| x.height())).unwrap_or(150.0),
+			),
+		};
+		let view_box = view_box.unwrap_or_else(|| Box2::new((0.0, 0.0).into(), (width, height).into()));
+		let aspect = root.attribute("preserveAspectRatio")
+			.map(parse_aspect_ratio)
+			.unwrap_or(AspectRatio { align: Some((0.5, 0.5)), slice: false });
+
+		let mut ids = HashMap::new();
+		collect_ids(&root, &mut ids);
+		let mut stylesheet = Stylesheet::default();
+		collect_stylesheets(&root, &mut stylesheet);
+
+		let mut builder = Builder {
+			ids,
+			stylesheet,
+			shapes: Vec::new(),
+			reference: view_box.size(),
+			elements: 0,
+			nesting: 0,
+		};
+		builder.add_children(&root, &Style::default(), &Transform::IDENTITY, 0)?;
+
+		Ok(Self {
+			shapes: builder.shapes,
+			view_box,
+			width: width.max(0.0),
+			height: height.max(0.0),
+			aspect,
+		})
+	}
+
+	/// Parse an SVG document from memory.
+	pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+		let data = std::str::from_utf8(data).map_err(|e| Error::InvalidSvg(e.to_string()))?;
+		Self::parse(data)
+	}
+
+	/// Load an SVG document from a file.
+	pub fn open(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+		Self::from_bytes(&std::fs::read(path)?)
+	}
+
+	/// Get the intrinsic size of the image.
+	///
+	/// The size is taken from the `width` and `height` of the root element,
+	/// where one pixel is 1/96th of an inch.
+	pub fn size(&self) -> Size2<Mm> {
+		Size2::new(self.width * MM_PER_PX, self.height * MM_PER_PX)
+	}
+
+	/// Get the aspect ratio of the image (width divided by height).
+	pub fn aspect_ratio(&self) -> f64 {
+		self.width / self.height
+	}
+
+	/// Get the size of the image when scaled to the given width, preserving the aspect ratio.
+	pub fn size_for_width(&self, width: Length<Mm>) -> Size2<Mm> {
+		Size2::new(width.get(), width.get() / self.aspect_ratio())
+	}
+
+	/// Get the size of the image when scaled to the given height, preserving the aspect ratio.
+	pub fn size_for_height(&self, height: Length<Mm>) -> Size2<Mm> {
+		Size2::new(height.get() * self.aspect_ratio(), height.get())
+	}
+
+	/// Get the transformation from SVG user units to points for the given viewport.
+	fn viewport_transform(&self, area: Box2<UnknownUnit>) -> Transform {
+		let scale_x = area.width() / self.view_box.width();
+		let scale_y = area.height() / self.view_box.height();
+		let (scale_x, scale_y, align_x, align_y) = match self.aspect.align {
+			None => (scale_x, scale_y, 0.0, 0.0),
+			Some((align_x, align_y)) => {
+				let scale = if self.aspect.slice { scale_x.max(scale_y) } else { scale_x.min(scale_y) };
+				(scale, scale, align_x, align_y)
+			},
+		};
+		let x = area.min.x + (area.width() - self.view_box.width() * scale_x) * align_x;
+		let y = area.min.y + (area.height() - self.view_box.height() * scale_y) * align_y;
+		Transform::translate(x, y)
+			.then(&Transform::scale(scale_x, scale_y))
+			.then(&Transform::translate(-self.view_box.min.x, -self.view_box.min.y))
+	}
+}
+
+impl Page {
+	/// Draw an SVG image with the given size at a position.
+	///
+	/// The image is anchored to the position the same way as a text box.
+	/// The view box of the image is fitted in the area according to its `preserveAspectRatio` attribute,
+	/// and anything outside of the area is clipped.
+	/// Use [`Svg::size_for_width`] or [`Svg::size_for_height`] to preserve the aspect ratio of the image.
+	///
+	/// Returns the area covered by the image.
+	pub fn draw_svg(&self, svg: &Svg, position: &BoxPosition, size: Size2<Mm>) -> Box2<Mm> {
+		let origin = position.point + position.alignment_offset(size, Length::new(size.height));
+		let area = Box2::new(origin, origin + size);
+		let area_pt = (area * PT_PER_MM).to_untyped();
+		if area_pt.is_empty() {
+			return area;
+		}
+
+		let viewport = svg.viewport_transform(area_pt);
+		self.cairo.save();
+		self.cairo.rectangle(area_pt.min.x, area_pt.min.y, area_pt.width(), area_pt.height());
+		self.cairo.clip();
+		for shape in &svg.shapes {
+			let transform = viewport.then(&shape.transform);
+			if transform.is_invertible() {
+				self.draw_svg_shape(shape, transform);
+			}
+		}
+		self.cairo.restore();
+		area
+	}
+
+	fn draw_svg_shape(&self, shape: &Shape, transform: Transform) {
+		self.cairo.save();
+		self.cairo.transform(transform.to_cairo());
+		self.cairo.new_path();
+		for command in &shape.commands {
+			match *command {
+				PathCommand::MoveTo(x, y) => self.cairo.move_to(x, y),
+				PathCommand::LineTo(x, y) => self.cairo.line_to(x, y),
+				PathCommand::CurveTo(x1, y1, x2, y2, x, y) => self.cairo.curve_to(x1, y1, x2, y2, x, y),
+				PathCommand::Close => self.cairo.close_path(),
+			}
+		}
+		if let Some((color, fill_rule)) = shape.fill {
+			color.set_as_source(&self.cairo);
+			self.cairo.set_fill_rule(fill_rule.to_cairo());
+			self.cairo.fill_preserve();
+		}
+		if let Some(stroke) = &shape.stroke {
+			stroke.color.set_as_source(&self.cairo);
+			self.cairo.set_line_width(stroke.width);
+			self.cairo.set_line_cap(stroke.cap.to_cairo());
+			self.cairo.set_line_join(stroke.join.to_cairo());
+			self.cairo.set_miter_limit(stroke.miter_limit);
+			self.cairo.set_dash(&stroke.dash, stroke.dash_offset);
+			self.cairo.stroke();
+		}
+		self.cairo.new_path();
+		self.cairo.restore();
+	}
+}
+
+/// Parse the value of a `preserveAspectRatio` attribute.
+fn parse_aspect_ratio(value: &str) -> AspectRatio {
+	let mut words = value.split_whitespace();
+	let align = match words.next().unwrap_or("xMidYMid") {
+		"none" => None,
+		align => {
+			let fraction = |x: &str| match x {
+				"Min" => 0.0,
+				"Max" => 1.0,
+				_ => 0.5,
+			};
+			match (align.get(1..4), align.get(5..8)) {
+				(Some(x), Some(y)) => Some((fraction(x), fraction(y))),
+				_ => Some((0.5, 0.5)),
+			}
+		},
+	};
+	let slice = words.next() == Some("slice");
+	AspectRatio { align, slice }
+}
+
+/// Collect all elements with an `id` attribute.
+fn collect_ids<'a>(element: &'a xml::Element, ids: &mut HashMap<&'a str, &'a xml::Element>) {
+	if let Some(id) = element.attribute("id") {
+		ids.entry(id).or_insert(element);
+	}
+	for child in &element.children {
+		collect_ids(child, ids);
+	}
+}
+
+/// Collect the rules of all CSS `<style>` elements.
+fn collect_stylesheets(element: &xml::Element, stylesheet: &mut Stylesheet) {
+	if element.name == "style" {
+		if matches!(element.attribute("type"), None | Some("text/css")) {
+			stylesheet.add(&element.text);
+		}
+		return;
+	}
+	for child in &element.children {
+		collect_stylesheets(child, stylesheet);
+	}
+}
+
+/// Flattens the element tree into a list of shapes.
+struct Builder<'a> {
+	ids: HashMap<&'a str, &'a xml::Element>,
+	stylesheet: Stylesheet,
+	shapes: Vec<Shape>,
+
+	/// The size of the view box, used to resolve percentages.
+	reference: Size2<UnknownUnit>,
+
+	/// The number of elements instantiated so far.
+	elements: usize,
+
+	/// The current nesting depth of elements.
+	nesting: usize,
+}
+
+impl<'a> Builder<'a> {
+	fn add_children(&mut self, element: &'a xml::Element, style: &Style, transform: &Transform, depth: usize) -> Result<(), Error> {
+		if self.nesting >= MAX_NESTING {
+			return Err(Error::InvalidSvg(format!("elements nested more than {} levels deep", MAX_NESTING)));
+		}
+		self.nesting += 1;
+		for child in &element.children {
+			self.add_element(child, style, transform, depth)?;
+		}
+		self.nesting -= 1;
+		Ok(())
+	}
+
+	fn add_element(&mut self, element: &'a xml::Element, parent_style: &Style, parent_transform: &Transform, depth: usize) -> Result<(), Error> {
+		self.elements += 1;
+		if self.elements > MAX_ELEMENTS {
+			return Err(Error::InvalidSvg(format!("more than {} elements after expanding references", MAX_ELEMENTS)));
+		}
+
+		let style = Style::for_element(parent_style, element, &self.stylesheet);
+		if !style.display {
+			return Ok(());
+		}
+		let mut transform = match element.attribute("transform") {
+			Some(x) => parent_transform.then(&style::parse_transform(x)),
+			None => *parent_transform,
+		};
+
+		let commands = match element.name.as_str() {
+			"g" | "a" | "switch" => {
+				return self.add_children(element, &style, &transform, depth);
+			},
+			"svg" => {
+				// Nested viewports are treated as groups, without clipping or a view box.
+				transform = transform.then(&Transform::translate(self.x(element, "x"), self.y(element, "y")));
+				return self.add_children(element, &style, &transform, depth);
+			},
+			"use" => {
+				let target = element.attribute("href")
+					.and_then(|x| x.strip_prefix('#'))
+					.and_then(|x| self.ids.get(x).copied());
+				if let Some(target) = target {
+					if depth < MAX_USE_DEPTH {
+						transform = transform.then(&Transform::translate(self.x(element, "x"), self.y(element, "y")));
+						if target.name == "symbol" {
+							self.add_children(target, &style, &transform, depth + 1)?;
+						} else {
+							self.nesting += 1;
+							self.add_element(target, &style, &transform, depth + 1)?;
+							self.nesting -= 1;
+						}
+					}
+				}
+				return Ok(());
+			},
+			"path" => element.attribute("d").map(path::parse_path_data).unwrap_or_default(),
+			"rect" => self.rect(element),
+			"circle" => {
+				let r = self.length(element, "r");
+				ellipse(self.x(element, "cx"), self.y(element, "cy"), r, r)
+			},
+			"ellipse" => ellipse(self.x(element, "cx"), self.y(element, "cy"), self.x(element, "rx"), self.y(element, "ry")),
+			"line" => vec![
+				PathCommand::MoveTo(self.x(element, "x1"), self.y(element, "y1")),
+				PathCommand::LineTo(self.x(element, "x2"), self.y(element, "y2")),
+			],
+			"polyline" | "polygon" => {
+				let points = element.attribute("points").map(style::parse_number_list).unwrap_or_default();
+				let mut commands: Vec<_> = points.chunks_exact(2).map(|x| PathCommand::LineTo(x[0], x[1])).collect();
+				if let Some(PathCommand::LineTo(x, y)) = commands.first().copied() {
+					commands[0] = PathCommand::MoveTo(x, y);
+					if element.name == "polygon" {
+						commands.push(PathCommand::Close);
+					}
+				}
+				commands
+			},
+			// Everything else is either a container that is not rendered directly (defs, symbol, clipPath, ...)
+			// or an element that is not supported (text, image, ...).
+			_ => return Ok(()),
+		};
+
+		if !style.visible || commands.is_empty() {
+			return Ok(());
+		}
+
+		let fill = style.fill_color().map(|color| (color, style.fill_rule));
+		let stroke = style.stroke_color().map(|color| Stroke {
+			color,
+			width: style.stroke_width,
+			cap: style.stroke_cap,
+			join: style.stroke_join,
+			miter_limit: style.stroke_miter_limit,
+			dash: style.stroke_dash.clone(),
+			dash_offset: style.stroke_dash_offset,
+		});
+		if fill.is_some() || stroke.is_some() {
+			self.shapes.push(Shape { transform, commands, fill, stroke });
+		}
+		Ok(())
+	}
+
+	/// Get a horizontal length attribute, resolving percentages against the view box width.
+	fn x(&self, element: &xml::Element, name: &str) -> f64 {
+		element.attribute(name).and_then(|x| style::parse_length(x, self.reference.width)).unwrap_or(0.0)
+	}
+
+	/// Get a vertical length attribute, resolving percentages against the view box height.
+	fn y(&self, element: &xml::Element, name: &str) -> f64 {
+		element.attribute(name).and_then(|x| style::parse_length(x, self.reference.height)).unwrap_or(0.0)
+	}
+
+	/// Get a length attribute that is neither horizontal nor vertical.
+	fn length(&self, element: &xml::Element, name: &str) -> f64 {
+		let reference = self.reference.width.hypot(self.reference.height) / std::f64::consts::SQRT_2;
+		element.attribute(name).and_then(|x| style::parse_length(x, reference)).unwrap_or(0.0)
+	}
+
+	fn rect(&self, element: &xml::Element) -> Vec<PathCommand> {
+		let x = self.x(element, "x");
+		let y = self.y(element, "y");
+		let width = self.x(element, "width");
+		let height = self.y(element, "height");
+		if width <= 0.0 || height <= 0.0 {
+			return Vec::new();
+		}
+
+		// A missing radius defaults to the other radius.
+		let rx = element.attribute("rx").and_then(|v| style::parse_length(v, self.reference.width));
+		let ry = element.attribute("ry").and_then(|v| style::parse_length(v, self.reference.height));
+		let (rx, ry) = match (rx, ry) {
+			(Some(rx), Some(ry)) => (rx, ry),
+			(Some(r), None) | (None, Some(r)) => (r, r),
+			(None, None) => (0.0, 0.0),
+		};
+		let rx = rx.max(0.0).min(width / 2.0);
+		let ry = ry.max(0.0).min(height / 2.0);
+
+		let mut commands = Vec::new();
+		if rx == 0.0 || ry == 0.0 {
+			commands.push(PathCommand::MoveTo(x, y));
+			commands.push(PathCommand::LineTo(x + width, y));
+			commands.push(PathCommand::LineTo(x + width, y + height));
+			commands.push(PathCommand::LineTo(x, y + height));
+		} else {
+			let arc = |commands: &mut Vec<PathCommand>, start, end| path::arc_to_curves(commands, start, (rx, ry), 0.0, false, true, end);
+			commands.push(PathCommand::MoveTo(x + rx, y));
+			commands.push(PathCommand::LineTo(x + width - rx, y));
+			arc(&mut commands, (x + width - rx, y), (x + width, y + ry));
+			commands.push(PathCommand::LineTo(x + width, y + height - ry));
+			arc(&mut commands, (x + width, y + height - ry), (x + width - rx, y + height));
+			commands.push(PathCommand::LineTo(x + rx, y + height));
+			arc(&mut commands, (x + rx, y + height), (x, y + height - ry));
+			commands.push(PathCommand::LineTo(x, y + ry));
+			arc(&mut commands, (x, y + ry), (x + rx, y));
+		}
+		commands.push(PathCommand::Close);
+		commands
+	}
+}
+
+/// Create the path commands for an ellipse.
+fn ellipse(cx: f64, cy: f64, rx: f64, ry: f64) -> Vec<PathCommand> {
+	if rx <= 0.0 || ry <= 0.0 {
+		return Vec::new();
+	}
+	let points = [(cx + rx, cy), (cx, cy + ry), (cx - rx, cy), (cx, cy - ry), (cx + rx, cy)];
+	let mut commands = vec![PathCommand::MoveTo(points[0].0, points[0].1)];
+	for pair in points.windows(2) {
+		path::arc_to_curves(&mut commands, pair[0], (rx, ry), 0.0, false, true, pair[1]);
+	}
+	commands.push(PathCommand::Close);
+	commands
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn parse(body: &str) -> Result<Svg, Error> {
+		Svg::parse(&format!(r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" viewBox="0 0 100 100">{}</svg>"#, body))
+	}
+
+	#[test]
+	fn size_from_view_box() {
+		let svg = Svg::parse(r#"<svg viewBox="0 0 200 100" width="96"/>"#).unwrap();
+		assert_eq!((svg.width, svg.height), (96.0, 48.0));
+		assert_eq!(svg.size(), Size2::new(25.4, 12.7));
+	}
+
+	#[test]
+	fn use_references() {
+		let svg = parse(r##"
+			<defs><rect id="r" width="10" height="10"/></defs>
+			<use href="#r"/>
+			<use xlink:href="#r" x="20"/>
+			<use xlink:href="#missing"/>
+		"##).unwrap();
+		assert_eq!(svg.shapes.len(), 2);
+		assert_eq!(svg.shapes[1].transform, Transform::translate(20.0, 0.0));
+	}
+
+	#[test]
+	fn use_reference_cycle() {
+		let svg = parse(r##"<g id="a"><rect width="1" height="1"/><use href="#a"/></g>"##).unwrap();
+		assert_eq!(svg.shapes.len(), MAX_USE_DEPTH + 1);
+	}
+
+	#[test]
+	fn exponential_use_expansion() {
+		// Each level references the previous level ten times, which would expand to 10^8 rectangles.
+		let mut body = String::from(r#"<defs><rect id="l0" width="1" height="1"/>"#);
+		for level in 1..=8 {
+			body.push_str(&format!(r#"<g id="l{}">"#, level));
+			for _ in 0..10 {
+				body.push_str(&format!(r##"<use href="#l{}"/>"##, level - 1));
+			}
+			body.push_str("</g>");
+		}
+		body.push_str(r##"</defs><use href="#l8"/>"##);
+		assert!(matches!(parse(&body), Err(Error::InvalidSvg(_))));
+	}
+
+	#[test]
+	fn nesting_through_references() {
+		// The document itself is not nested too deeply, but the nesting through references is.
+		let groups = |depth| format!("{}{}", "<g>".repeat(depth), "</g>".repeat(depth));
+		let mut body = format!(r#"<defs><g id="l0">{}</g>"#, groups(100));
+		for level in 1..10 {
+			body.push_str(&format!(r##"<g id="l{}">{}<use href="#l{}"/>{}</g>"##, level, "<g>".repeat(100), level - 1, "</g>".repeat(100)));
+		}
+		body.push_str(r##"</defs><use href="#l9"/>"##);
+		assert!(matches!(parse(&body), Err(Error::InvalidSvg(_))));
+	}
+
+	#[test]
+	fn style_sheet() {
+		let svg = parse(r#"
+			<style type="text/css"><![CDATA[ .st0 { fill: #FF0000 } ]]></style>
+			<rect class="st0" width="10" height="10"/>
+		"#).unwrap();
+		assert_eq!(svg.shapes[0].fill.map(|(color, _)| color), Some(Color::rgb8(255, 0, 0)));
+	}
+
+	#[test]
+	fn invalid_documents() {
+		assert!(matches!(Svg::parse("<html/>"), Err(Error::InvalidSvg(_))));
+		assert!(matches!(Svg::parse("<svg>"), Err(Error::InvalidSvg(_))));
+		assert!(matches!(Svg::from_bytes(b"<svg>\xFF</svg>"), Err(Error::InvalidSvg(_))));
+	}
+}
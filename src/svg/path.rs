@@ -0,0 +1,547 @@
+//! Parsing of SVG path data into absolute lines and cubic Bézier curves.
+
+use std::f64::consts::PI;
+
+/// A path command in absolute user units.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) enum PathCommand {
+	MoveTo(f64, f64),
+	LineTo(f64, f64),
+	CurveTo(f64, f64, f64, f64, f64, f64),
+	Close,
+}
+
+/// Scanner for the numbers, flags and command letters in path data and number lists.
+pub(crate) struct Scanner<'a> {
+	data: &'a [u8],
+	pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+	pub(crate) fn new(data: &'a str) -> Self {
+		Self {
+			data: data.as_bytes(),
+			pos: 0,
+		}
+	}
+
+	pub(crate) fn at_end(&mut self) -> bool {
+		self.skip_separators();
+		self.pos >= self.data.len()
+	}
+
+	fn skip_separators(&mut self) {
+		while let Some(c) = self.data.get(self.pos) {
+			if c.is_ascii_whitespace() || *c == b',' {
+				self.pos += 1;
+			} else {
+				break;
+			}
+		}
+	}
+
+	/// Parse a command letter, if the next token is one.
+	fn command(&mut self) -> Option<u8> {
+		self.skip_separators();
+		let c = *self.data.get(self.pos)?;
+		if c.is_ascii_alphabetic() && c != b'e' && c != b'E' {
+			self.pos += 1;
+			Some(c)
+		} else {
+			None
+		}
+	}
+
+	/// Parse a number.
+	pub(crate) fn number(&mut self) -> Option<f64> {
+		self.skip_separators();
+		let start = self.pos;
+		let mut end = start;
+		let digits = |data: &[u8], mut i: usize| {
+			while data.get(i).is_some_and(u8::is_ascii_digit) {
+				i += 1;
+			}
+			i
+		};
+
+		if let Some(b'+') | Some(b'-') = self.data.get(end) {
+			end += 1;
+		}
+		let integer_end = digits(self.data, end);
+		let mut mantissa_end = integer_end;
+		if self.data.get(integer_end) == Some(&b'.') {
+			mantissa_end = digits(self.data, integer_end + 1);
+			if mantissa_end == integer_end + 1 && integer_end == end {
+				return None;
+			}
+		} else if integer_end == end {
+			return None;
+		}
+		end = mantissa_end;
+
+		// Only consume an exponent if it is followed by digits, so units like `em` are not mistaken for one.
+		if let Some(b'e') | Some(b'E') = self.data.get(end) {
+			let mut exponent = end + 1;
+			if let Some(b'+') | Some(b'-') = self.data.get(exponent) {
+				exponent += 1;
+			}
+			let exponent_end = digits(self.data, exponent);
+			if exponent_end > exponent {
+				end = exponent_end;
+			}
+		}
+
+		let value = std::str::from_utf8(&self.data[start..end]).ok()?.parse().ok()?;
+		self.pos = end;
+		Some(value)
+	}
+
+	/// Parse a pair of numbers.
+	pub(crate) fn pair(&mut self) -> Option<(f64, f64)> {
+		let x = self.number()?;
+		let y = self.number()?;
+		Some((x, y))
+	}
+
+	/// Parse an arc flag, which may be written without separators.
+	fn flag(&mut self) -> Option<bool> {
+		self.skip_separators();
+		let flag = match self.data.get(self.pos)? {
+			b'0' => false,
+			b'1' => true,
+			_ => return None,
+		};
+		self.pos += 1;
+		Some(flag)
+	}
+
+	/// Get the remaining unparsed data.
+	pub(crate) fn rest(&self) -> &'a [u8] {
+		&self.data[self.pos..]
+	}
+}
+
+/// Parse SVG path data.
+///
+/// As required by the SVG specification, the path is rendered up to the first error in the data.
+pub(crate) fn parse_path_data(data: &str) -> Vec<PathCommand> {
+	let mut scanner = Scanner::new(data);
+	let mut path = Vec::new();
+	let mut current = (0.0, 0.0);
+	let mut start = (0.0, 0.0);
+	let mut last_cubic: Option<(f64, f64)> = None;
+	let mut last_quad: Option<(f64, f64)> = None;
+	let mut command: Option<u8> = None;
+
+	while !scanner.at_end() {
+		if let Some(c) = scanner.command() {
+			command = Some(c);
+		}
+		let c = match command {
+			Some(c) => c,
+			None => break,
+		};
+		// Path data must start with a move-to command.
+		if path.is_empty() && !c.eq_ignore_ascii_case(&b'M') {
+			break;
+		}
+
+		let (ox, oy) = if c.is_ascii_lowercase() { current } else { (0.0, 0.0) };
+		let mut cubic = None;
+		let mut quad = None;
+		match c.to_ascii_uppercase() {
+			b'M' => {
+				let (x, y) = match scanner.pair() {
+					Some(x) => x,
+					None => break,
+				};
+				current = (x + ox, y + oy);
+				start = current;
+				path.push(PathCommand::MoveTo(current.0, current.1));
+				// Subsequent coordinate pairs are implicit line-to commands.
+				command = Some(if c == b'm' { b'l' } else { b'L' });
+			},
+			b'L' => {
+				let (x, y) = match scanner.pair() {
+					Some(x) => x,
+					None => break,
+				};
+				current = (x + ox, y + oy);
+				path.push(PathCommand::LineTo(current.0, current.1));
+			},
+			b'H' => {
+				let x = match scanner.number() {
+					Some(x) => x,
+					None => break,
+				};
+				current.0 = x + ox;
+				path.push(PathCommand::LineTo(current.0, current.1));
+			},
+			b'V' => {
+				let y = match scanner.number() {
+					Some(x) => x,
+					None => break,
+				};
+				current.1 = y + oy;
+				path.push(PathCommand::LineTo(current.0, current.1));
+			},
+			b'C' => {
+				let ((x1, y1), (x2, y2), (x, y)) = match (scanner.pair(), scanner.pair(), scanner.pair()) {
+					(Some(a), Some(b), Some(c)) => (a, b, c),
+					_ => break,
+				};
+				let control = (x2 + ox, y2 + oy);
+				current = (x + ox, y + oy);
+				path.push(PathCommand::CurveTo(x1 + ox, y1 + oy, control.0, control.1, current.0, current.1));
+				cubic = Some(control);
+			},
+			b'S' => {
+				let ((x2, y2), (x, y)) = match (scanner.pair(), scanner.pair()) {
+					(Some(a), Some(b)) => (a, b),
+					_ => break,
+				};
+				let (x1, y1) = reflect(last_cubic, current);
+				let control = (x2 + ox, y2 + oy);
+				current = (x + ox, y + oy);
+				path.push(PathCommand::CurveTo(x1, y1, control.0, control.1, current.0, current.1));
+				cubic = Some(control);
+			},
+			b'Q' => {
+				let ((x1, y1), (x, y)) = match (scanner.pair(), scanner.pair()) {
+					(Some(a), Some(b)) => (a, b),
+					_ => break,
+				};
+				let control = (x1 + ox, y1 + oy);
+				let end = (x + ox, y + oy);
+				path.push(quad_to_cubic(current, control, end));
+				current = end;
+				quad = Some(control);
+			},
+			b'T' => {
+				let (x, y) = match scanner.pair() {
+					Some(x) => x,
+					None => break,
+				};
+				let control = reflect(last_quad, current);
+				let end = (x + ox, y + oy);
+				path.push(quad_to_cubic(current, control, end));
+				current = end;
+				quad = Some(control);
+			},
+			b'A' => {
+				let arc = (|| {
+					let (rx, ry) = scanner.pair()?;
+					let rotation = scanner.number()?;
+					let large_arc = scanner.flag()?;
+					let sweep = scanner.flag()?;
+					let (x, y) = scanner.pair()?;
+					Some((rx, ry, rotation, large_arc, sweep, (x + ox, y + oy)))
+				})();
+				let (rx, ry, rotation, large_arc, sweep, end) = match arc {
+					Some(x) => x,
+					None => break,
+				};
+				arc_to_curves(&mut path, current, (rx, ry), rotation, large_arc, sweep, end);
+				current = end;
+			},
+			b'Z' => {
+				path.push(PathCommand::Close);
+				current = start;
+				// A close command must be followed by a new command.
+				command = None;
+			},
+			_ => break,
+		}
+		last_cubic = cubic;
+		last_quad = quad;
+	}
+
+	path
+}
+
+/// Reflect a control point around the current point.
+///
+/// If there is no previous control point, the current point is used.
+fn reflect(control: Option<(f64, f64)>, current: (f64, f64)) -> (f64, f64) {
+	match control {
+		Some((x, y)) => (2.0 * current.0 - x, 2.0 * current.1 - y),
+		None => current,
+	}
+}
+
+/// Convert a quadratic Bézier curve to a cubic Bézier curve.
+fn quad_to_cubic(start: (f64, f64), control: (f64, f64), end: (f64, f64)) -> PathCommand {
+	let x1 = start.0 + 2.0 / 3.0 * (control.0 - start.0);
+	let y1 = start.1 + 2.0 / 3.0 * (control.1 - start.1);
+	let x2 = end.0 + 2.0 / 3.0 * (control.0 - end.0);
+	let y2 = end.1 + 2.0 / 3.0 * (control.1 - end.1);
+	PathCommand::CurveTo(x1, y1, x2, y2, end.0, end.1)
+}
+
+/// Approximate an elliptical arc with cubic Bézier curves.
+///
+/// This follows the endpoint to center parameterization conversion from the SVG specification.
+pub(crate) fn arc_to_curves(
+	path: &mut Vec<PathCommand>,
+	start: (f64, f64),
+	radii: (f64, f64),
+	rotation: f64,
+	large_arc: bool,
+	sweep: bool,
+	end: (f64, f64),
+) {
+	if start == end {
+		return;
+	}
+	let (mut rx, mut ry) = (radii.0.abs(), radii.1.abs());
+	if rx == 0.0 || ry == 0.0 {
+		path.push(PathCommand::LineTo(end.0, end.1));
+		return;
+	}
+
+	let (sin, cos) = rotation.to_radians().sin_cos();
+	let dx = (start.0 - end.0) / 2.0;
+	let dy = (start.1 - end.1) / 2.0;
+	let x1 = cos * dx + sin * dy;
+	let y1 = -sin * dx + cos * dy;
+
+	// Scale up the radii if they are too small to reach the end point.
+	let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+	if lambda > 1.0 {
+		rx *= lambda.sqrt();
+		ry *= lambda.sqrt();
+	}
+
+	let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+	let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+	let mut coefficient = (numerator / denominator).max(0.0).sqrt();
+	if large_arc == sweep {
+		coefficient = -coefficient;
+	}
+	let cx1 = coefficient * rx * y1 / ry;
+	let cy1 = -coefficient * ry * x1 / rx;
+	let cx = cos * cx1 - sin * cy1 + (start.0 + end.0) / 2.0;
+	let cy = sin * cx1 + cos * cy1 + (start.1 + end.1) / 2.0;
+
+	let angle = |ux: f64, uy: f64, vx: f64, vy: f64| (ux * vy - uy * vx).atan2(ux * vx + uy * vy);
+	let ux = (x1 - cx1) / rx;
+	let uy = (y1 - cy1) / ry;
+	let vx = (-x1 - cx1) / rx;
+	let vy = (-y1 - cy1) / ry;
+	let theta = angle(1.0, 0.0, ux, uy);
+	let mut delta = angle(ux, uy, vx, vy);
+	if !sweep && delta > 0.0 {
+		delta -= 2.0 * PI;
+	} else if sweep && delta < 0.0 {
+		delta += 2.0 * PI;
+	}
+
+	// Map a point on the unit circle to the ellipse.
+	let map = |x: f64, y: f64| (
+		cx + rx * x * cos - ry * y * sin,
+		cy + rx * x * sin + ry * y * cos,
+	);
+
+	let segments = (delta.abs() / (PI / 2.0)).ceil().max(1.0) as usize;
+	let step = delta / segments as f64;
+	let t = 4.0 / 3.0 * (step / 4.0).tan();
+	for i in 0..segments {
+		let (sin1, cos1) = (theta + step * i as f64).sin_cos();
+		let (sin2, cos2) = (theta + step * (i + 1) as f64).sin_cos();
+		let c1 = map(cos1 - t * sin1, sin1 + t * cos1);
+		let c2 = map(cos2 + t * sin2, sin2 - t * cos2);
+		let point = if i + 1 == segments { end } else { map(cos2, sin2) };
+		path.push(PathCommand::CurveTo(c1.0, c1.1, c2.0, c2.1, point.0, point.1));
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use PathCommand::{Close, CurveTo, LineTo, MoveTo};
+
+	fn assert_near(actual: (f64, f64), expected: (f64, f64)) {
+		let close = (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9;
+		assert!(close, "expected {:?}, got {:?}", expected, actual);
+	}
+
+	fn end_point(command: &PathCommand) -> (f64, f64) {
+		match *command {
+			MoveTo(x, y) | LineTo(x, y) | CurveTo(_, _, _, _, x, y) => (x, y),
+			Close => panic!("close command has no end point"),
+		}
+	}
+
+	#[test]
+	fn absolute_commands() {
+		assert_eq!(parse_path_data("M 10 20 L 30 40 H 50 V 60 Z"), [
+			MoveTo(10.0, 20.0),
+			LineTo(30.0, 40.0),
+			LineTo(50.0, 40.0),
+			LineTo(50.0, 60.0),
+			Close,
+		]);
+	}
+
+	#[test]
+	fn relative_commands() {
+		assert_eq!(parse_path_data("m 10 20 l 5 5 h 10 v -10 z m 1 1 l 1 0"), [
+			MoveTo(10.0, 20.0),
+			LineTo(15.0, 25.0),
+			LineTo(25.0, 25.0),
+			LineTo(25.0, 15.0),
+			Close,
+			// After closing a path, relative commands are relative to the start of the closed path.
+			MoveTo(11.0, 21.0),
+			LineTo(12.0, 21.0),
+		]);
+	}
+
+	#[test]
+	fn implicit_commands() {
+		// Extra pairs after a move-to are line-to commands, other commands simply repeat.
+		assert_eq!(parse_path_data("M 0 0 1 1 2 2 L 3 3 4 4"), [
+			MoveTo(0.0, 0.0),
+			LineTo(1.0, 1.0),
+			LineTo(2.0, 2.0),
+			LineTo(3.0, 3.0),
+			LineTo(4.0, 4.0),
+		]);
+		assert_eq!(parse_path_data("m 1 1 1 1 h 1 1"), [
+			MoveTo(1.0, 1.0),
+			LineTo(2.0, 2.0),
+			LineTo(3.0, 2.0),
+			LineTo(4.0, 2.0),
+		]);
+	}
+
+	#[test]
+	fn compact_numbers() {
+		assert_eq!(parse_path_data("M1-2.5.5 3L-.5-.5"), [
+			MoveTo(1.0, -2.5),
+			LineTo(0.5, 3.0),
+			LineTo(-0.5, -0.5),
+		]);
+	}
+
+	#[test]
+	fn exponents() {
+		assert_eq!(parse_path_data("M1e2 1E-1L2e+1,1e-5"), [
+			MoveTo(100.0, 0.1),
+			LineTo(20.0, 1e-5),
+		]);
+	}
+
+	#[test]
+	fn exponent_versus_unit() {
+		let mut scanner = Scanner::new("1em");
+		assert_eq!(scanner.number(), Some(1.0));
+		assert_eq!(scanner.rest(), b"em");
+
+		let mut scanner = Scanner::new("1e-5em");
+		assert_eq!(scanner.number(), Some(1e-5));
+		assert_eq!(scanner.rest(), b"em");
+
+		let mut scanner = Scanner::new("2ex");
+		assert_eq!(scanner.number(), Some(2.0));
+		assert_eq!(scanner.rest(), b"ex");
+	}
+
+	#[test]
+	fn stops_at_first_error() {
+		assert_eq!(parse_path_data("M 0 0 L 10 10 L 5"), [MoveTo(0.0, 0.0), LineTo(10.0, 10.0)]);
+		assert_eq!(parse_path_data("M 0 0 L 10 10 X 5 5"), [MoveTo(0.0, 0.0), LineTo(10.0, 10.0)]);
+		assert_eq!(parse_path_data("L 10 10"), []);
+		assert_eq!(parse_path_data("10 10"), []);
+	}
+
+	#[test]
+	fn smooth_curves() {
+		let path = parse_path_data("M 0 0 C 0 10 10 10 10 0 S 20 -10 20 0");
+		assert_eq!(path[2], CurveTo(10.0, -10.0, 20.0, -10.0, 20.0, 0.0));
+
+		// Quadratic curves are converted to cubic curves.
+		let path = parse_path_data("M 0 0 Q 3 3 6 0 T 12 0");
+		assert_eq!(path[1], CurveTo(2.0, 2.0, 4.0, 2.0, 6.0, 0.0));
+		assert_eq!(path[2], CurveTo(8.0, -2.0, 10.0, -2.0, 12.0, 0.0));
+	}
+
+	#[test]
+	fn arc_command() {
+		let path = parse_path_data("M 0 0 A 10 10 0 0 1 20 0");
+		assert_eq!(path.len(), 3);
+		assert_near(end_point(&path[1]), (10.0, -10.0));
+		assert_eq!(end_point(&path[2]), (20.0, 0.0));
+	}
+
+	#[test]
+	fn arc_compact_flags() {
+		assert_eq!(parse_path_data("M0 0A10 10 0 0120 0"), parse_path_data("M 0 0 A 10 10 0 0 1 20 0"));
+		assert_eq!(parse_path_data("M0 0a10 10 0 0120 0"), parse_path_data("M 0 0 A 10 10 0 0 1 20 0"));
+	}
+
+	#[test]
+	fn arc_zero_radius() {
+		assert_eq!(parse_path_data("M 0 0 A 0 10 0 0 1 20 0"), [MoveTo(0.0, 0.0), LineTo(20.0, 0.0)]);
+	}
+
+	#[test]
+	fn arc_quarter_circle() {
+		let mut path = Vec::new();
+		arc_to_curves(&mut path, (1.0, 0.0), (1.0, 1.0), 0.0, false, true, (0.0, 1.0));
+		let k = 4.0 / 3.0 * (PI / 8.0).tan();
+		match path.as_slice() {
+			&[CurveTo(x1, y1, x2, y2, x, y)] => {
+				assert_near((x1, y1), (1.0, k));
+				assert_near((x2, y2), (k, 1.0));
+				assert_eq!((x, y), (0.0, 1.0));
+			},
+			path => panic!("expected a single curve, got {:?}", path),
+		}
+	}
+
+	#[test]
+	fn arc_sweep_and_large_arc() {
+		// The four combinations of flags select the four possible arcs between two points.
+		let mid = |large_arc, sweep| {
+			let mut path = Vec::new();
+			arc_to_curves(&mut path, (0.0, 0.0), (1.0, 1.0), 0.0, large_arc, sweep, (1.0, 1.0));
+			(path.len(), end_point(&path[0]))
+		};
+		assert_eq!(mid(false, true).0, 1);
+		assert_eq!(mid(false, false).0, 1);
+		assert_eq!(mid(true, true).0, 3);
+		assert_eq!(mid(true, false).0, 3);
+		assert_near(mid(true, true).1, (1.0, -1.0));
+		assert_near(mid(true, false).1, (-1.0, 1.0));
+	}
+
+	#[test]
+	fn arc_radii_scaled_up() {
+		// The radii are too small to reach the end point, so they are scaled up to a half circle.
+		let mut path = Vec::new();
+		arc_to_curves(&mut path, (0.0, 0.0), (0.5, 0.5), 0.0, false, true, (2.0, 0.0));
+		assert_eq!(path.len(), 2);
+		assert_near(end_point(&path[0]), (1.0, -1.0));
+	}
+
+	#[test]
+	fn arc_rotated_ellipse() {
+		// An ellipse rotated by 90 degrees is the same as one with swapped radii.
+		let mut rotated = Vec::new();
+		arc_to_curves(&mut rotated, (0.0, 0.0), (2.0, 1.0), 90.0, false, true, (0.0, 4.0));
+		let mut swapped = Vec::new();
+		arc_to_curves(&mut swapped, (0.0, 0.0), (1.0, 2.0), 0.0, false, true, (0.0, 4.0));
+		assert_eq!(rotated.len(), swapped.len());
+		for (a, b) in rotated.iter().zip(&swapped) {
+			assert_near(end_point(a), end_point(b));
+		}
+	}
+
+	#[test]
+	fn arc_to_same_point() {
+		let mut path = Vec::new();
+		arc_to_curves(&mut path, (1.0, 1.0), (1.0, 1.0), 0.0, false, true, (1.0, 1.0));
+		assert_eq!(path, []);
+	}
+}
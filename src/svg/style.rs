@@ -0,0 +1,792 @@
+//! Parsing of SVG attribute values: colors, lengths, transforms, presentation attributes and style sheets.
+
+use super::path::Scanner;
+use super::xml::Element;
+use crate::{Color, LineCap, LineJoin};
+
+/// An affine transformation matrix, using the same layout as SVG `matrix(a b c d e f)`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub(crate) struct Transform {
+	pub(crate) a: f64,
+	pub(crate) b: f64,
+	pub(crate) c: f64,
+	pub(crate) d: f64,
+	pub(crate) e: f64,
+	pub(crate) f: f64,
+}
+
+impl Transform {
+	pub(crate) const IDENTITY: Self = Self::new(1.0, 0.0, 0.0, 1.0, 0.0, 0.0);
+
+	pub(crate) const fn new(a: f64, b: f64, c: f64, d: f64, e: f64, f: f64) -> Self {
+		Self { a, b, c, d, e, f }
+	}
+
+	pub(crate) fn translate(x: f64, y: f64) -> Self {
+		Self::new(1.0, 0.0, 0.0, 1.0, x, y)
+	}
+
+	pub(crate) fn scale(x: f64, y: f64) -> Self {
+		Self::new(x, 0.0, 0.0, y, 0.0, 0.0)
+	}
+
+	pub(crate) fn rotate(degrees: f64) -> Self {
+		let (sin, cos) = degrees.to_radians().sin_cos();
+		Self::new(cos, sin, -sin, cos, 0.0, 0.0)
+	}
+
+	/// Get the transformation that applies `other` first and then `self`.
+	pub(crate) fn then(&self, other: &Self) -> Self {
+		Self::new(
+			self.a * other.a + self.c * other.b,
+			self.b * other.a + self.d * other.b,
+			self.a * other.c + self.c * other.d,
+			self.b * other.c + self.d * other.d,
+			self.a * other.e + self.c * other.f + self.e,
+			self.b * other.e + self.d * other.f + self.f,
+		)
+	}
+
+	/// Check if the transformation can be inverted.
+	///
+	/// Cairo puts a context in an error state when a singular matrix is used.
+	pub(crate) fn is_invertible(&self) -> bool {
+		let determinant = self.a * self.d - self.b * self.c;
+		determinant != 0.0 && determinant.is_finite() && self.e.is_finite() && self.f.is_finite()
+	}
+
+	pub(crate) fn to_cairo(self) -> cairo::Matrix {
+		cairo::Matrix::new(self.a, self.b, self.c, self.d, self.e, self.f)
+	}
+}
+
+/// Parse a transform list, such as `translate(10 20) rotate(45)`.
+///
+/// Parsing stops at the first invalid transform.
+pub(crate) fn parse_transform(value: &str) -> Transform {
+	let mut transform = Transform::IDENTITY;
+	let mut rest = value.trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+	while let Some(open) = rest.find('(') {
+		let name = rest[..open].trim();
+		let close = match rest[open..].find(')') {
+			Some(x) => open + x,
+			None => break,
+		};
+		let args = parse_number_list(&rest[open + 1..close]);
+		let next = match (name, args.as_slice()) {
+			("matrix", &[a, b, c, d, e, f]) => Transform::new(a, b, c, d, e, f),
+			("translate", &[x]) => Transform::translate(x, 0.0),
+			("translate", &[x, y]) => Transform::translate(x, y),
+			("scale", &[x]) => Transform::scale(x, x),
+			("scale", &[x, y]) => Transform::scale(x, y),
+			("rotate", &[angle]) => Transform::rotate(angle),
+			("rotate", &[angle, x, y]) => Transform::translate(x, y)
+				.then(&Transform::rotate(angle))
+				.then(&Transform::translate(-x, -y)),
+			("skewX", &[angle]) => Transform::new(1.0, 0.0, angle.to_radians().tan(), 1.0, 0.0, 0.0),
+			("skewY", &[angle]) => Transform::new(1.0, angle.to_radians().tan(), 0.0, 1.0, 0.0, 0.0),
+			_ => break,
+		};
+		transform = transform.then(&next);
+		rest = rest[close + 1..].trim_start_matches(|c: char| c.is_whitespace() || c == ',');
+	}
+	transform
+}
+
+/// Parse a list of numbers separated by whitespace and/or commas.
+///
+/// Parsing stops at the first invalid number.
+pub(crate) fn parse_number_list(value: &str) -> Vec<f64> {
+	let mut scanner = Scanner::new(value);
+	let mut numbers = Vec::new();
+	while let Some(number) = scanner.number() {
+		numbers.push(number);
+	}
+	numbers
+}
+
+/// Parse a length and convert it to user units.
+///
+/// Percentages are resolved relative to `reference`.
+pub(crate) fn parse_length(value: &str, reference: f64) -> Option<f64> {
+	let mut scanner = Scanner::new(value.trim());
+	let number = scanner.number()?;
+	let unit = std::str::from_utf8(scanner.rest()).ok()?;
+	let scale = match unit {
+		"" | "px" => 1.0,
+		"pt" => 96.0 / 72.0,
+		"pc" => 96.0 / 6.0,
+		"mm" => 96.0 / 25.4,
+		"cm" => 96.0 / 2.54,
+		"in" => 96.0,
+		"em" => 16.0,
+		"ex" => 8.0,
+		"%" => reference / 100.0,
+		_ => return None,
+	};
+	Some(number * scale)
+}
+
+/// A fill or stroke paint.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum Paint {
+	None,
+	Color(Color),
+	CurrentColor,
+}
+
+/// Parse a paint value.
+///
+/// Paint servers such as gradients are not supported.
+/// For those, the fallback color is used if there is one.
+fn parse_paint(value: &str) -> Option<Paint> {
+	let value = value.trim();
+	if value == "none" {
+		Some(Paint::None)
+	} else if value == "currentColor" {
+		Some(Paint::CurrentColor)
+	} else if let Some(rest) = value.strip_prefix("url(") {
+		let fallback = rest.find(')').map(|i| rest[i + 1..].trim()).unwrap_or("");
+		if fallback.is_empty() {
+			Some(Paint::None)
+		} else {
+			parse_paint(fallback)
+		}
+	} else {
+		parse_color(value).map(Paint::Color)
+	}
+}
+
+/// Parse a color value.
+pub(crate) fn parse_color(value: &str) -> Option<Color> {
+	let value = value.trim();
+	if let Some(hex) = value.strip_prefix('#') {
+		if !hex.bytes().all(|x| x.is_ascii_hexdigit()) {
+			return None;
+		}
+		let digit = |i: usize| u8::from_str_radix(hex.get(i..i + 1)?, 16).ok();
+		let byte = |i: usize| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok();
+		match hex.len() {
+			3 => Some(Color::rgb8(digit(0)? * 17, digit(1)? * 17, digit(2)? * 17)),
+			6 => Some(Color::rgb8(byte(0)?, byte(2)?, byte(4)?)),
+			_ => None,
+		}
+	} else if let Some(args) = value.strip_prefix("rgb(").or_else(|| value.strip_prefix("rgba(")) {
+		let args = args.strip_suffix(')')?;
+		let mut components = args.split(',').map(str::trim);
+		let mut component = || -> Option<f64> {
+			let component = components.next()?;
+			match component.strip_suffix('%') {
+				Some(percentage) => percentage.trim().parse::<f64>().ok().map(|x| x / 100.0),
+				None => component.parse::<f64>().ok().map(|x| x / 255.0),
+			}
+		};
+		let color = Color::rgb(component()?, component()?, component()?);
+		let alpha = components.next().and_then(|x| x.parse().ok()).unwrap_or(1.0);
+		Some(color.with_alpha(alpha))
+	} else {
+		let name = value.to_ascii_lowercase();
+		NAMED_COLORS
+			.binary_search_by_key(&name.as_str(), |&(name, _)| name)
+			.ok()
+			.map(|i| {
+				let (red, green, blue) = NAMED_COLORS[i].1;
+				Color::rgb8(red, green, blue)
+			})
+	}
+}
+
+/// The fill rule of a shape.
+#[derive(Debug, Copy, Clone)]
+pub(crate) enum FillRule {
+	NonZero,
+	EvenOdd,
+}
+
+impl FillRule {
+	pub(crate) fn to_cairo(self) -> cairo::FillRule {
+		match self {
+			Self::NonZero => cairo::FillRule::Winding,
+			Self::EvenOdd => cairo::FillRule::EvenOdd,
+		}
+	}
+}
+
+/// The computed style of an element.
+#[derive(Debug, Clone)]
+pub(crate) struct Style {
+	pub(crate) fill: Paint,
+	pub(crate) fill_opacity: f64,
+	pub(crate) fill_rule: FillRule,
+	pub(crate) stroke: Paint,
+	pub(crate) stroke_opacity: f64,
+	pub(crate) stroke_width: f64,
+	pub(crate) stroke_cap: LineCap,
+	pub(crate) stroke_join: LineJoin,
+	pub(crate) stroke_miter_limit: f64,
+	pub(crate) stroke_dash: Vec<f64>,
+	pub(crate) stroke_dash_offset: f64,
+	pub(crate) color: Color,
+
+	/// The combined opacity of the element and all its ancestors.
+	pub(crate) opacity: f64,
+
+	pub(crate) display: bool,
+	pub(crate) visible: bool,
+}
+
+impl Default for Style {
+	fn default() -> Self {
+		Self {
+			fill: Paint::Color(Color::BLACK),
+			fill_opacity: 1.0,
+			fill_rule: FillRule::NonZero,
+			stroke: Paint::None,
+			stroke_opacity: 1.0,
+			stroke_width: 1.0,
+			stroke_cap: LineCap::Butt,
+			stroke_join: LineJoin::Miter,
+			stroke_miter_limit: 4.0,
+			stroke_dash: Vec::new(),
+			stroke_dash_offset: 0.0,
+			color: Color::BLACK,
+			opacity: 1.0,
+			display: true,
+			visible: true,
+		}
+	}
+}
+
+impl Style {
+	/// Compute the style of an element from the style of its parent.
+	///
+	/// Presentation attributes are applied first, followed by the matching rules from the style sheet,
+	/// and finally the declarations from the `style` attribute.
+	pub(crate) fn for_element(parent: &Style, element: &Element, stylesheet: &Stylesheet) -> Self {
+		let mut style = Self {
+			opacity: parent.opacity,
+			display: true,
+			..parent.clone()
+		};
+		for (name, value) in &element.attributes {
+			if name != "style" {
+				style.apply_property(name, value);
+			}
+		}
+		for (name, value) in stylesheet.declarations(element) {
+			style.apply_property(name, value);
+		}
+		if let Some(value) = element.attribute("style") {
+			for (name, value) in parse_declarations(value) {
+				style.apply_property(&name, &value);
+			}
+		}
+		style
+	}
+
+	/// Apply a single property to the style.
+	///
+	/// Invalid and unsupported values are ignored.
+	fn apply_property(&mut self, name: &str, value: &str) {
+		let value = value.trim();
+		if value == "inherit" {
+			return;
+		}
+
+		let number = || value.parse::<f64>().ok();
+		let opacity = || match value.strip_suffix('%') {
+			Some(percentage) => percentage.parse::<f64>().ok().map(|x| x / 100.0),
+			None => number(),
+		}.map(|x| x.clamp(0.0, 1.0));
+
+		match name {
+			"fill" => if let Some(x) = parse_paint(value) { self.fill = x },
+			"fill-opacity" => if let Some(x) = opacity() { self.fill_opacity = x },
+			"fill-rule" => match value {
+				"nonzero" => self.fill_rule = FillRule::NonZero,
+				"evenodd" => self.fill_rule = FillRule::EvenOdd,
+				_ => (),
+			},
+			"stroke" => if let Some(x) = parse_paint(value) { self.stroke = x },
+			"stroke-opacity" => if let Some(x) = opacity() { self.stroke_opacity = x },
+			"stroke-width" => if let Some(x) = parse_length(value, 0.0) { self.stroke_width = x.max(0.0) },
+			"stroke-linecap" => match value {
+				"butt" => self.stroke_cap = LineCap::Butt,
+				"round" => self.stroke_cap = LineCap::Round,
+				"square" => self.stroke_cap = LineCap::Square,
+				_ => (),
+			},
+			"stroke-linejoin" => match value {
+				"miter" => self.stroke_join = LineJoin::Miter,
+				"round" => self.stroke_join = LineJoin::Round,
+				"bevel" => self.stroke_join = LineJoin::Bevel,
+				_ => (),
+			},
+			"stroke-miterlimit" => if let Some(x) = number() { self.stroke_miter_limit = x.max(1.0) },
+			"stroke-dasharray" => {
+				if value == "none" {
+					self.stroke_dash = Vec::new();
+				} else {
+					let dash = parse_number_list(value);
+					if dash.iter().all(|&x| x >= 0.0) && dash.iter().any(|&x| x > 0.0) {
+						self.stroke_dash = dash;
+					} else {
+						self.stroke_dash = Vec::new();
+					}
+				}
+			},
+			"stroke-dashoffset" => if let Some(x) = parse_length(value, 0.0) { self.stroke_dash_offset = x },
+			"color" => if let Some(x) = parse_color(value) { self.color = x },
+			"opacity" => if let Some(x) = opacity() { self.opacity *= x },
+			"display" => self.display = value != "none",
+			"visibility" => self.visible = value == "visible",
+			_ => (),
+		}
+	}
+
+	/// Resolve a paint to a color, taking the opacity into account.
+	fn resolve(&self, paint: Paint, opacity: f64) -> Option<Color> {
+		let color = match paint {
+			Paint::None => return None,
+			Paint::Color(color) => color,
+			Paint::CurrentColor => self.color,
+		};
+		Some(color.with_alpha(color.alpha * opacity * self.opacity))
+	}
+
+	/// Get the fill color, if the element is filled.
+	pub(crate) fn fill_color(&self) -> Option<Color> {
+		self.resolve(self.fill, self.fill_opacity)
+	}
+
+	/// Get the stroke color, if the element is stroked.
+	pub(crate) fn stroke_color(&self) -> Option<Color> {
+		if self.stroke_width <= 0.0 {
+			return None;
+		}
+		self.resolve(self.stroke, self.stroke_opacity)
+	}
+}
+
+/// The rules from the `<style>` elements of a document.
+///
+/// Only rules with simple selectors are supported: a type (`rect`), a class (`.name`), an ID (`#name`) or `*`.
+/// Rules with other selectors, such as combinators, attribute selectors or pseudo-classes, are ignored,
+/// as are at-rules like `@media`.
+#[derive(Debug, Default)]
+pub(crate) struct Stylesheet {
+	rules: Vec<Rule>,
+}
+
+#[derive(Debug)]
+struct Rule {
+	selector: Selector,
+	declarations: Vec<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+enum Selector {
+	Universal,
+	Type(String),
+	Class(String),
+	Id(String),
+}
+
+impl Stylesheet {
+	/// Add the rules from a CSS style sheet.
+	pub(crate) fn add(&mut self, css: &str) {
+		let css = strip_comments(css);
+		let mut rest = css.as_str();
+		while let Some(open) = rest.find('{') {
+			// Skip at-rules without a block, like `@import`.
+			if rest.trim_start().starts_with('@') {
+				if let Some(end) = rest.find(';').filter(|&end| end < open) {
+					rest = &rest[end + 1..];
+					continue;
+				}
+			}
+
+			let selectors = rest[..open].trim();
+			rest = &rest[open + 1..];
+			if selectors.starts_with('@') {
+				// Skip the whole block of the at-rule, including any nested blocks.
+				let mut depth = 1;
+				let end = rest.char_indices().find(|&(_, c)| {
+					match c {
+						'{' => depth += 1,
+						'}' => depth -= 1,
+						_ => (),
+					}
+					depth == 0
+				});
+				rest = end.map(|(i, _)| &rest[i + 1..]).unwrap_or("");
+				continue;
+			}
+
+			let close = rest.find('}').unwrap_or(rest.len());
+			let declarations = parse_declarations(&rest[..close]);
+			rest = rest.get(close + 1..).unwrap_or("");
+			for selector in selectors.split(',').filter_map(Selector::parse) {
+				self.rules.push(Rule {
+					selector,
+					declarations: declarations.clone(),
+				});
+			}
+		}
+	}
+
+	/// Get the declarations of all rules that match an element.
+	///
+	/// The declarations are ordered by the specificity of their selector,
+	/// and by their order in the style sheet for equal specificity.
+	fn declarations<'a>(&'a self, element: &Element) -> impl Iterator<Item = (&'a str, &'a str)> {
+		let mut rules: Vec<_> = self.rules.iter()
+			.filter(|rule| rule.selector.matches(element))
+			.collect();
+		rules.sort_by_key(|rule| rule.selector.specificity());
+		rules.into_iter()
+			.flat_map(|rule| rule.declarations.iter())
+			.map(|(name, value)| (name.as_str(), value.as_str()))
+	}
+}
+
+impl Selector {
+	fn parse(selector: &str) -> Option<Self> {
+		let selector = selector.trim();
+		let is_name = |x: &str| !x.is_empty() && x.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_');
+		if selector == "*" {
+			Some(Self::Universal)
+		} else if let Some(class) = selector.strip_prefix('.') {
+			is_name(class).then(|| Self::Class(class.to_string()))
+		} else if let Some(id) = selector.strip_prefix('#') {
+			is_name(id).then(|| Self::Id(id.to_string()))
+		} else {
+			is_name(selector).then(|| Self::Type(selector.to_string()))
+		}
+	}
+
+	fn specificity(&self) -> u8 {
+		match self {
+			Self::Universal => 0,
+			Self::Type(_) => 1,
+			Self::Class(_) => 2,
+			Self::Id(_) => 3,
+		}
+	}
+
+	fn matches(&self, element: &Element) -> bool {
+		match self {
+			Self::Universal => true,
+			Self::Type(name) => element.name == *name,
+			Self::Class(class) => element.attribute("class")
+				.is_some_and(|x| x.split_whitespace().any(|x| x == class)),
+			Self::Id(id) => element.attribute("id") == Some(id.as_str()),
+		}
+	}
+}
+
+/// Parse a list of CSS declarations, such as the value of a `style` attribute.
+///
+/// The `!important` flag is ignored.
+fn parse_declarations(value: &str) -> Vec<(String, String)> {
+	value.split(';')
+		.filter_map(|declaration| declaration.split_once(':'))
+		.map(|(name, value)| {
+			let value = value.trim();
+			let value = value.strip_suffix("!important").unwrap_or(value);
+			(name.trim().to_string(), value.trim().to_string())
+		})
+		.collect()
+}
+
+/// Remove CSS comments.
+fn strip_comments(css: &str) -> String {
+	let mut stripped = String::with_capacity(css.len());
+	let mut rest = css;
+	while let Some(start) = rest.find("/*") {
+		stripped.push_str(&rest[..start]);
+		rest = match rest[start + 2..].find("*/") {
+			Some(end) => &rest[start + 2 + end + 2..],
+			None => "",
+		};
+	}
+	stripped.push_str(rest);
+	stripped
+}
+
+/// The CSS named colors, sorted by name.
+const NAMED_COLORS: &[(&str, (u8, u8, u8))] = &[
+	("aliceblue", (240, 248, 255)),
+	("antiquewhite", (250, 235, 215)),
+	("aqua", (0, 255, 255)),
+	("aquamarine", (127, 255, 212)),
+	("azure", (240, 255, 255)),
+	("beige", (245, 245, 220)),
+	("bisque", (255, 228, 196)),
+	("black", (0, 0, 0)),
+	("blanchedalmond", (255, 235, 205)),
+	("blue", (0, 0, 255)),
+	("blueviolet", (138, 43, 226)),
+	("brown", (165, 42, 42)),
+	("burlywood", (222, 184, 135)),
+	("cadetblue", (95, 158, 160)),
+	("chartreuse", (127, 255, 0)),
+	("chocolate", (210, 105, 30)),
+	("coral", (255, 127, 80)),
+	("cornflowerblue", (100, 149, 237)),
+	("cornsilk", (255, 248, 220)),
+	("crimson", (220, 20, 60)),
+	("cyan", (0, 255, 255)),
+	("darkblue", (0, 0, 139)),
+	("darkcyan", (0, 139, 139)),
+	("darkgoldenrod", (184, 134, 11)),
+	("darkgray", (169, 169, 169)),
+	("darkgreen", (0, 100, 0)),
+	("darkgrey", (169, 169, 169)),
+	("darkkhaki", (189, 183, 107)),
+	("darkmagenta", (139, 0, 139)),
+	("darkolivegreen", (85, 107, 47)),
+	("darkorange", (255, 140, 0)),
+	("darkorchid", (153, 50, 204)),
+	("darkred", (139, 0, 0)),
+	("darksalmon", (233, 150, 122)),
+	("darkseagreen", (143, 188, 143)),
+	("darkslateblue", (72, 61, 139)),
+	("darkslategray", (47, 79, 79)),
+	("darkslategrey", (47, 79, 79)),
+	("darkturquoise", (0, 206, 209)),
+	("darkviolet", (148, 0, 211)),
+	("deeppink", (255, 20, 147)),
+	("deepskyblue", (0, 191, 255)),
+	("dimgray", (105, 105, 105)),
+	("dimgrey", (105, 105, 105)),
+	("dodgerblue", (30, 144, 255)),
+	("firebrick", (178, 34, 34)),
+	("floralwhite", (255, 250, 240)),
+	("forestgreen", (34, 139, 34)),
+	("fuchsia", (255, 0, 255)),
+	("gainsboro", (220, 220, 220)),
+	("ghostwhite", (248, 248, 255)),
+	("gold", (255, 215, 0)),
+	("goldenrod", (218, 165, 32)),
+	("gray", (128, 128, 128)),
+	("green", (0, 128, 0)),
+	("greenyellow", (173, 255, 47)),
+	("grey", (128, 128, 128)),
+	("honeydew", (240, 255, 240)),
+	("hotpink", (255, 105, 180)),
+	("indianred", (205, 92, 92)),
+	("indigo", (75, 0, 130)),
+	("ivory", (255, 255, 240)),
+	("khaki", (240, 230, 140)),
+	("lavender", (230, 230, 250)),
+	("lavenderblush", (255, 240, 245)),
+	("lawngreen", (124, 252, 0)),
+	("lemonchiffon", (255, 250, 205)),
+	("lightblue", (173, 216, 230)),
+	("lightcoral", (240, 128, 128)),
+	("lightcyan", (224, 255, 255)),
+	("lightgoldenrodyellow", (250, 250, 210)),
+	("lightgray", (211, 211, 211)),
+	("lightgreen", (144, 238, 144)),
+	("lightgrey", (211, 211, 211)),
+	("lightpink", (255, 182, 193)),
+	("lightsalmon", (255, 160, 122)),
+	("lightseagreen", (32, 178, 170)),
+	("lightskyblue", (135, 206, 250)),
+	("lightslategray", (119, 136, 153)),
+	("lightslategrey", (119, 136, 153)),
+	("lightsteelblue", (176, 196, 222)),
+	("lightyellow", (255, 255, 224)),
+	("lime", (0, 255, 0)),
+	("limegreen", (50, 205, 50)),
+	("linen", (250, 240, 230)),
+	("magenta", (255, 0, 255)),
+	("maroon", (128, 0, 0)),
+	("mediumaquamarine", (102, 205, 170)),
+	("mediumblue", (0, 0, 205)),
+	("mediumorchid", (186, 85, 211)),
+	("mediumpurple", (147, 112, 219)),
+	("mediumseagreen", (60, 179, 113)),
+	("mediumslateblue", (123, 104, 238)),
+	("mediumspringgreen", (0, 250, 154)),
+	("mediumturquoise", (72, 209, 204)),
+	("mediumvioletred", (199, 21, 133)),
+	("midnightblue", (25, 25, 112)),
+	("mintcream", (245, 255, 250)),
+	("mistyrose", (255, 228, 225)),
+	("moccasin", (255, 228, 181)),
+	("navajowhite", (255, 222, 173)),
+	("navy", (0, 0, 128)),
+	("oldlace", (253, 245, 230)),
+	("olive", (128, 128, 0)),
+	("olivedrab", (107, 142, 35)),
+	("orange", (255, 165, 0)),
+	("orangered", (255, 69, 0)),
+	("orchid", (218, 112, 214)),
+	("palegoldenrod", (238, 232, 170)),
+	("palegreen", (152, 251, 152)),
+	("paleturquoise", (175, 238, 238)),
+	("palevioletred", (219, 112, 147)),
+	("papayawhip", (255, 239, 213)),
+	("peachpuff", (255, 218, 185)),
+	("peru", (205, 133, 63)),
+	("pink", (255, 192, 203)),
+	("plum", (221, 160, 221)),
+	("powderblue", (176, 224, 230)),
+	("purple", (128, 0, 128)),
+	("rebeccapurple", (102, 51, 153)),
+	("red", (255, 0, 0)),
+	("rosybrown", (188, 143, 143)),
+	("royalblue", (65, 105, 225)),
+	("saddlebrown", (139, 69, 19)),
+	("salmon", (250, 128, 114)),
+	("sandybrown", (244, 164, 96)),
+	("seagreen", (46, 139, 87)),
+	("seashell", (255, 245, 238)),
+	("sienna", (160, 82, 45)),
+	("silver", (192, 192, 192)),
+	("skyblue", (135, 206, 235)),
+	("slateblue", (106, 90, 205)),
+	("slategray", (112, 128, 144)),
+	("slategrey", (112, 128, 144)),
+	("snow", (255, 250, 250)),
+	("springgreen", (0, 255, 127)),
+	("steelblue", (70, 130, 180)),
+	("tan", (210, 180, 140)),
+	("teal", (0, 128, 128)),
+	("thistle", (216, 191, 216)),
+	("tomato", (255, 99, 71)),
+	("turquoise", (64, 224, 208)),
+	("violet", (238, 130, 238)),
+	("wheat", (245, 222, 179)),
+	("white", (255, 255, 255)),
+	("whitesmoke", (245, 245, 245)),
+	("yellow", (255, 255, 0)),
+	("yellowgreen", (154, 205, 50)),
+];
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::svg::xml;
+
+	fn fill_of(css: &str, element: &str) -> Paint {
+		let mut stylesheet = Stylesheet::default();
+		stylesheet.add(css);
+		let element = xml::parse(element).unwrap();
+		Style::for_element(&Style::default(), &element, &stylesheet).fill
+	}
+
+	fn assert_fill(paint: Paint, expected: Color) {
+		match paint {
+			Paint::Color(color) => assert_eq!(color, expected),
+			paint => panic!("expected color {:?}, got {:?}", expected, paint),
+		}
+	}
+
+	#[test]
+	fn class_selector() {
+		let css = ".st0 { fill: #FF0000; } .st1 { fill: blue }";
+		assert_fill(fill_of(css, r#"<path class="st0"/>"#), Color::rgb8(255, 0, 0));
+		assert_fill(fill_of(css, r#"<path class="other st1"/>"#), Color::rgb8(0, 0, 255));
+		assert_fill(fill_of(css, r#"<path class="st2"/>"#), Color::BLACK);
+	}
+
+	#[test]
+	fn selector_lists_and_comments() {
+		let css = "/* exported */ .a, .b { fill: red; stroke: none } /* .b { fill: blue } */";
+		assert_fill(fill_of(css, r#"<rect class="b"/>"#), Color::rgb8(255, 0, 0));
+	}
+
+	#[test]
+	fn specificity() {
+		let css = "#x { fill: red } .c { fill: lime } rect { fill: blue } * { fill: white }";
+		assert_fill(fill_of(css, r#"<rect id="x" class="c"/>"#), Color::rgb8(255, 0, 0));
+		assert_fill(fill_of(css, r#"<rect class="c"/>"#), Color::rgb8(0, 255, 0));
+		assert_fill(fill_of(css, r#"<rect/>"#), Color::rgb8(0, 0, 255));
+		assert_fill(fill_of(css, r#"<circle/>"#), Color::rgb8(255, 255, 255));
+	}
+
+	#[test]
+	fn cascade_order() {
+		// Style sheet rules override presentation attributes, the style attribute overrides both.
+		let css = ".c { fill: red }";
+		assert_fill(fill_of(css, r#"<rect class="c" fill="blue"/>"#), Color::rgb8(255, 0, 0));
+		assert_fill(fill_of(css, r#"<rect class="c" style="fill: lime"/>"#), Color::rgb8(0, 255, 0));
+	}
+
+	#[test]
+	fn unsupported_rules_are_ignored() {
+		let css = "@import url(x.css); @media print { .c { fill: red } } g > .c { fill: blue } .c:hover { fill: lime } .c { fill: yellow }";
+		assert_fill(fill_of(css, r#"<rect class="c"/>"#), Color::rgb8(255, 255, 0));
+	}
+
+	#[test]
+	fn hex_colors() {
+		assert_eq!(parse_color("#f00"), Some(Color::rgb8(255, 0, 0)));
+		assert_eq!(parse_color("#FF8000"), Some(Color::rgb8(255, 128, 0)));
+		assert_eq!(parse_color(" #08f "), Some(Color::rgb8(0, 136, 255)));
+		assert_eq!(parse_color("#ff"), None);
+		assert_eq!(parse_color("#ff00"), None);
+		assert_eq!(parse_color("#gg0000"), None);
+		assert_eq!(parse_color("#+f+f+f"), None);
+	}
+
+	#[test]
+	fn functional_colors() {
+		assert_eq!(parse_color("rgb(255, 0, 0)"), Some(Color::rgb8(255, 0, 0)));
+		assert_eq!(parse_color("rgb(100%, 0%, 50%)"), Some(Color::rgb(1.0, 0.0, 0.5)));
+		assert_eq!(parse_color("rgba(0, 0, 255, 0.5)"), Some(Color::rgb8(0, 0, 255).with_alpha(0.5)));
+		assert_eq!(parse_color("rgb(255, 0)"), None);
+		assert_eq!(parse_color("rgb(255, 0, 0"), None);
+	}
+
+	#[test]
+	fn named_colors() {
+		assert_eq!(parse_color("red"), Some(Color::rgb8(255, 0, 0)));
+		assert_eq!(parse_color("CornflowerBlue"), Some(Color::rgb8(100, 149, 237)));
+		assert_eq!(parse_color("notacolor"), None);
+		assert!(NAMED_COLORS.windows(2).all(|x| x[0].0 < x[1].0), "named colors must be sorted for binary search");
+	}
+
+	#[test]
+	fn paints() {
+		assert!(matches!(parse_paint("none"), Some(Paint::None)));
+		assert!(matches!(parse_paint("currentColor"), Some(Paint::CurrentColor)));
+		assert!(matches!(parse_paint("url(#gradient)"), Some(Paint::None)));
+		assert_eq!(parse_paint("url(#gradient) #00f").and_then(|x| match x { Paint::Color(x) => Some(x), _ => None }), Some(Color::rgb8(0, 0, 255)));
+	}
+
+	#[test]
+	fn lengths() {
+		assert_eq!(parse_length("10", 0.0), Some(10.0));
+		assert_eq!(parse_length("10px", 0.0), Some(10.0));
+		assert_eq!(parse_length("1in", 0.0), Some(96.0));
+		assert_eq!(parse_length("2em", 0.0), Some(32.0));
+		assert_eq!(parse_length("1e1em", 0.0), Some(160.0));
+		assert_eq!(parse_length("50%", 300.0), Some(150.0));
+		assert_eq!(parse_length("10 px", 0.0), None);
+		assert_eq!(parse_length("10furlong", 0.0), None);
+	}
+
+	#[test]
+	fn transforms() {
+		assert_eq!(parse_transform("translate(10 20) scale(2)"), Transform::new(2.0, 0.0, 0.0, 2.0, 10.0, 20.0));
+		assert_eq!(parse_transform("matrix(1,2,3,4,5,6)"), Transform::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0));
+		// Parsing stops at the first invalid transform.
+		assert_eq!(parse_transform("translate(10) bogus(1) scale(2)"), Transform::translate(10.0, 0.0));
+	}
+
+	#[test]
+	fn unbalanced_transform_parentheses() {
+		assert_eq!(parse_transform(")translate(1)"), Transform::IDENTITY);
+		assert_eq!(parse_transform("scale(2) )x("), Transform::scale(2.0, 2.0));
+		assert_eq!(parse_transform("translate(1"), Transform::IDENTITY);
+	}
+
+	#[test]
+	fn important_is_ignored() {
+		assert_fill(fill_of(".c { fill: red !important }", r#"<rect class="c"/>"#), Color::rgb8(255, 0, 0));
+	}
+}
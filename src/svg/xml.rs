@@ -0,0 +1,334 @@
+//! Minimal XML parser, just enough to read SVG documents.
+//!
+//! Comments, processing instructions and doctype declarations are skipped.
+//! Text content is only kept as a single string per element, mixed content is not preserved.
+//! Namespace prefixes are stripped from element and attribute names,
+//! so `xlink:href` can be found as `href`.
+
+/// Maximum nesting depth of elements, to avoid overflowing the stack on malicious input.
+const MAX_DEPTH: usize = 256;
+
+/// An XML element with its attributes and child elements.
+pub(crate) struct Element {
+	pub(crate) name: String,
+	pub(crate) attributes: Vec<(String, String)>,
+	pub(crate) children: Vec<Element>,
+
+	/// The text content of the element, including CDATA sections but not the text of child elements.
+	pub(crate) text: String,
+}
+
+impl Element {
+	/// Get the value of an attribute.
+	pub(crate) fn attribute(&self, name: &str) -> Option<&str> {
+		self.attributes
+			.iter()
+			.find(|(key, _)| key == name)
+			.map(|(_, value)| value.as_str())
+	}
+}
+
+/// Parse an XML document and return the root element.
+pub(crate) fn parse(data: &str) -> Result<Element, String> {
+	let mut parser = Parser { data, pos: 0 };
+	parser.skip_misc()?;
+	let root = parser.parse_element(0)?;
+	parser.skip_misc()?;
+	if !parser.rest().is_empty() {
+		return Err(parser.error("unexpected data after root element"));
+	}
+	Ok(root)
+}
+
+struct Parser<'a> {
+	data: &'a str,
+	pos: usize,
+}
+
+impl<'a> Parser<'a> {
+	fn rest(&self) -> &'a str {
+		&self.data[self.pos..]
+	}
+
+	fn error(&self, message: &str) -> String {
+		format!("{} at byte offset {}", message, self.pos)
+	}
+
+	fn skip_whitespace(&mut self) {
+		let rest = self.rest();
+		self.pos += rest.len() - rest.trim_start().len();
+	}
+
+	fn skip_until(&mut self, end: &str) -> Result<(), String> {
+		match self.rest().find(end) {
+			Some(i) => {
+				self.pos += i + end.len();
+				Ok(())
+			},
+			None => Err(self.error(&format!("missing `{}`", end))),
+		}
+	}
+
+	/// Skip whitespace, comments, processing instructions and doctype declarations.
+	fn skip_misc(&mut self) -> Result<(), String> {
+		loop {
+			self.skip_whitespace();
+			let rest = self.rest();
+			if rest.starts_with("<?") {
+				self.skip_until("?>")?;
+			} else if rest.starts_with("<!--") {
+				self.skip_until("-->")?;
+			} else if rest.starts_with("<!DOCTYPE") {
+				self.skip_doctype()?;
+			} else {
+				return Ok(());
+			}
+		}
+	}
+
+	fn skip_doctype(&mut self) -> Result<(), String> {
+		// The doctype may contain an internal subset in square brackets.
+		let mut depth = 0;
+		for (i, c) in self.rest().char_indices() {
+			match c {
+				'[' => depth += 1,
+				']' => depth -= 1,
+				'>' if depth == 0 => {
+					self.pos += i + 1;
+					return Ok(());
+				},
+				_ => (),
+			}
+		}
+		Err(self.error("unterminated doctype declaration"))
+	}
+
+	fn parse_name(&mut self) -> Result<&'a str, String> {
+		let rest = self.rest();
+		let end = rest
+			.find(|c: char| c.is_whitespace() || c == '/' || c == '>' || c == '=')
+			.unwrap_or(rest.len());
+		if end == 0 {
+			return Err(self.error("expected name"));
+		}
+		self.pos += end;
+		Ok(&rest[..end])
+	}
+
+	fn expect(&mut self, token: &str) -> Result<(), String> {
+		if self.rest().starts_with(token) {
+			self.pos += token.len();
+			Ok(())
+		} else {
+			Err(self.error(&format!("expected `{}`", token)))
+		}
+	}
+
+	fn parse_element(&mut self, depth: usize) -> Result<Element, String> {
+		if depth >= MAX_DEPTH {
+			return Err(self.error(&format!("elements nested more than {} levels deep", MAX_DEPTH)));
+		}
+		self.expect("<")?;
+		let name = local_name(self.parse_name()?).to_string();
+
+		let mut attributes = Vec::new();
+		loop {
+			self.skip_whitespace();
+			if self.rest().starts_with("/>") {
+				self.pos += 2;
+				return Ok(Element {
+					name,
+					attributes,
+					children: Vec::new(),
+					text: String::new(),
+				});
+			}
+			if self.rest().starts_with('>') {
+				self.pos += 1;
+				break;
+			}
+
+			let key = local_name(self.parse_name()?).to_string();
+			self.skip_whitespace();
+			self.expect("=")?;
+			self.skip_whitespace();
+			let quote = match self.rest().chars().next() {
+				Some(c @ '"') | Some(c @ '\'') => c,
+				_ => return Err(self.error("expected quoted attribute value")),
+			};
+			self.pos += 1;
+			let end = self.rest()
+				.find(quote)
+				.ok_or_else(|| self.error("unterminated attribute value"))?;
+			let value = decode_entities(&self.rest()[..end]);
+			self.pos += end + 1;
+			attributes.push((key, value));
+		}
+
+		let mut children = Vec::new();
+		let mut text = String::new();
+		loop {
+			let next = self.rest()
+				.find('<')
+				.ok_or_else(|| self.error(&format!("unterminated element `{}`", name)))?;
+			text.push_str(&decode_entities(&self.rest()[..next]));
+			self.pos += next;
+
+			let rest = self.rest();
+			if rest.starts_with("</") {
+				self.pos += 2;
+				if local_name(self.parse_name()?) != name {
+					return Err(self.error(&format!("mismatched end tag for element `{}`", name)));
+				}
+				self.skip_whitespace();
+				self.expect(">")?;
+				return Ok(Element {
+					name,
+					attributes,
+					children,
+					text,
+				});
+			} else if rest.starts_with("<!--") {
+				self.skip_until("-->")?;
+			} else if let Some(cdata) = rest.strip_prefix("<![CDATA[") {
+				let end = cdata.find("]]>")
+					.ok_or_else(|| self.error("missing `]]>`"))?;
+				text.push_str(&cdata[..end]);
+				self.pos += "<![CDATA[".len() + end + "]]>".len();
+			} else if rest.starts_with("<?") {
+				self.skip_until("?>")?;
+			} else {
+				children.push(self.parse_element(depth + 1)?);
+			}
+		}
+	}
+}
+
+/// Strip the namespace prefix from a name.
+fn local_name(name: &str) -> &str {
+	match name.rfind(':') {
+		Some(i) => &name[i + 1..],
+		None => name,
+	}
+}
+
+/// Decode the predefined entities and character references in a string.
+fn decode_entities(value: &str) -> String {
+	let mut decoded = String::with_capacity(value.len());
+	let mut rest = value;
+	while let Some(start) = rest.find('&') {
+		decoded.push_str(&rest[..start]);
+		rest = &rest[start..];
+
+		let replacement = rest.find(';').and_then(|end| {
+			let entity = &rest[1..end];
+			let c = match entity {
+				"amp" => Some('&'),
+				"lt" => Some('<'),
+				"gt" => Some('>'),
+				"quot" => Some('"'),
+				"apos" => Some('\''),
+				_ if entity.starts_with("#x") => u32::from_str_radix(&entity[2..], 16).ok().and_then(std::char::from_u32),
+				_ if entity.starts_with('#') => entity[1..].parse().ok().and_then(std::char::from_u32),
+				_ => None,
+			};
+			c.map(|c| (c, end + 1))
+		});
+
+		match replacement {
+			Some((c, len)) => {
+				decoded.push(c);
+				rest = &rest[len..];
+			},
+			None => {
+				decoded.push('&');
+				rest = &rest[1..];
+			},
+		}
+	}
+	decoded.push_str(rest);
+	decoded
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn elements_and_attributes() {
+		let root = parse(r#"<svg width="10" height='20'><g id="a"><rect x="1"/></g><circle r="2"></circle></svg>"#).unwrap();
+		assert_eq!(root.name, "svg");
+		assert_eq!(root.attribute("width"), Some("10"));
+		assert_eq!(root.attribute("height"), Some("20"));
+		assert_eq!(root.attribute("x"), None);
+		assert_eq!(root.children.len(), 2);
+		assert_eq!(root.children[0].name, "g");
+		assert_eq!(root.children[0].children[0].name, "rect");
+		assert_eq!(root.children[0].children[0].attribute("x"), Some("1"));
+		assert_eq!(root.children[1].name, "circle");
+		assert!(root.children[1].children.is_empty());
+	}
+
+	#[test]
+	fn namespace_prefixes() {
+		let root = parse(r##"<svg:svg xmlns:svg="http://www.w3.org/2000/svg"><svg:use xlink:href="#a"/></svg:svg>"##).unwrap();
+		assert_eq!(root.name, "svg");
+		assert_eq!(root.children[0].name, "use");
+		assert_eq!(root.children[0].attribute("href"), Some("#a"));
+	}
+
+	#[test]
+	fn prolog_and_misc() {
+		let data = r#"<?xml version="1.0"?>
+			<!-- comment -->
+			<!DOCTYPE svg PUBLIC "-//W3C//DTD SVG 1.1//EN" "x.dtd" [ <!ENTITY ns "x"> ]>
+			<svg><?pi data?><!-- <rect/> --><g/></svg>
+			<!-- trailing comment -->
+		"#;
+		let root = parse(data).unwrap();
+		assert_eq!(root.name, "svg");
+		assert_eq!(root.children.len(), 1);
+		assert_eq!(root.children[0].name, "g");
+	}
+
+	#[test]
+	fn text_content() {
+		let root = parse("<svg><style>.a { fill: red }<![CDATA[ .b > .c { fill: &blue } ]]>&amp;</style></svg>").unwrap();
+		assert_eq!(root.children[0].text, ".a { fill: red } .b > .c { fill: &blue } &");
+	}
+
+	#[test]
+	fn errors() {
+		assert!(parse("").is_err());
+		assert!(parse("<svg>").is_err());
+		assert!(parse("<svg></g>").is_err());
+		assert!(parse("<svg/><svg/>").is_err());
+		assert!(parse("<svg width=10/>").is_err());
+		assert!(parse(r#"<svg width="10/>"#).is_err());
+		assert!(parse("<svg><!-- </svg>").is_err());
+	}
+
+	#[test]
+	fn nesting_limit() {
+		let nested = |depth| format!("{}{}", "<g>".repeat(depth), "</g>".repeat(depth));
+		assert!(parse(&nested(MAX_DEPTH)).is_ok());
+		assert!(parse(&nested(MAX_DEPTH + 1)).is_err());
+		assert!(parse(&nested(100_000)).is_err());
+	}
+
+	#[test]
+	fn entities() {
+		assert_eq!(decode_entities("a &amp; b"), "a & b");
+		assert_eq!(decode_entities("&lt;&gt;&quot;&apos;"), "<>\"'");
+		assert_eq!(decode_entities("&#65;&#x42;&#x1F600;"), "AB\u{1F600}");
+	}
+
+	#[test]
+	fn invalid_entities_are_kept() {
+		assert_eq!(decode_entities("a & b"), "a & b");
+		assert_eq!(decode_entities("&nbsp;"), "&nbsp;");
+		assert_eq!(decode_entities("&#xD800;&#xZZ;&#;"), "&#xD800;&#xZZ;&#;");
+		assert_eq!(decode_entities("&amp"), "&amp");
+		assert_eq!(decode_entities("&&amp;"), "&&");
+	}
+}
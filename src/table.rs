@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+
 use crate::{
+	Box2,
 	BoxPosition,
+	Color,
+	Error,
 	Length,
 	Margins,
 	Mm,
@@ -8,42 +13,122 @@ use crate::{
 	PdfWriter,
 	Point2,
 	Pt,
-	PT_PER_MM,
+	ShapeStyle,
 	Size2,
+	StrokeStyle,
+	TableBorders,
+	Text,
 	TextAlign,
 	TextBox,
 	TextStyle,
 	Vector2,
+	VerticalAnchor,
 	mm,
 	pt,
 };
 
 pub struct Table {
 	position: BoxPosition,
-	cell_padding: Margins<Mm>,
 	columns: Vec<ColumnSpec>,
 	cells: Vec<TableCell>,
 
 	size: Size2<Mm>,
 	column_widths: Vec<Length<Mm>>,
+
+	/// The top-left corner of the table.
+	origin: Point2<Mm>,
+
+	/// The top of each row relative to the top of the table, followed by the bottom of the last row.
+	row_tops: Vec<Length<Mm>>,
+
+	/// The index of the cell covering each grid position, in row major order.
+	grid: Vec<Vec<Option<usize>>>,
+
+	borders: TableBorders,
+	backgrounds: Backgrounds,
 }
 
 struct TableCell {
 	text: TextBox,
-	alignment: TextAlign,
+	alignment: CellAlign,
+
+	/// The width of the text before the decimal separator, for decimal aligned cells.
+	decimal_offset: Option<Length<Mm>>,
+
+	row: usize,
+	column: usize,
+	row_span: usize,
+	column_span: usize,
+	vertical_align: VerticalAnchor,
 }
 
-pub struct TableBuilder<'a> {
-	pdf_writer: &'a PdfWriter,
+pub struct TableBuilder<'a, W> {
+	pdf_writer: &'a PdfWriter<W>,
 	max_width: Length<Mm>,
 	position: BoxPosition,
 	cell_padding: Margins<Mm>,
 	columns: Vec<ColumnSpec>,
 	cells: Vec<TableCell>,
+	borders: TableBorders,
+	backgrounds: Backgrounds,
+	vertical_aligns: HashMap<(usize, usize), VerticalAnchor>,
+	aligns: HashMap<(usize, usize), CellAlign>,
+}
+
+/// Horizontal alignment of the text in a table cell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CellAlign {
+	Left,
+	Center,
+	Right,
+
+	/// Align numbers on a decimal separator.
+	///
+	/// The separators of all decimal aligned cells in a column line up,
+	/// and the aligned numbers are placed against the right side of the column.
+	/// Text without the separator is aligned as if the separator directly follows it.
+	/// Decimal aligned text is not wrapped.
+	Decimal(char),
 }
 
-impl<'a> TableBuilder<'a> {
-	pub fn new(pdf_writer: &'a PdfWriter, max_width: Length<Mm>) -> Self {
+impl From<TextAlign> for CellAlign {
+	fn from(other: TextAlign) -> Self {
+		match other {
+			TextAlign::Left => Self::Left,
+			TextAlign::Center => Self::Center,
+			TextAlign::Right => Self::Right,
+		}
+	}
+}
+
+/// Background colors of cells, rows and columns.
+#[derive(Default)]
+struct Backgrounds {
+	cells: HashMap<(usize, usize), Color>,
+	rows: HashMap<usize, Color>,
+	columns: HashMap<usize, Color>,
+
+	/// Color for alternating rows, and the first row to apply it to.
+	stripes: Option<(Color, usize)>,
+}
+
+impl Backgrounds {
+	/// Get the background of a grid position, not counting cell backgrounds.
+	///
+	/// Row backgrounds take precedence over alternating rows, followed by column backgrounds.
+	fn band(&self, row: usize, column: usize) -> Option<Color> {
+		let stripe = self.stripes
+			.filter(|&(_, first)| row >= first && (row - first) & 1 == 0)
+			.map(|(color, _)| color);
+		self.rows.get(&row)
+			.copied()
+			.or(stripe)
+			.or_else(|| self.columns.get(&column).copied())
+	}
+}
+
+impl<'a, W> TableBuilder<'a, W> {
+	pub fn new(pdf_writer: &'a PdfWriter<W>, max_width: Length<Mm>) -> Self {
 		Self {
 			pdf_writer,
 			max_width,
@@ -51,6 +136,10 @@ impl<'a> TableBuilder<'a> {
 			cell_padding: Margins::vh(pt(1.0) * MM_PER_PT, pt(4.0) * MM_PER_PT),
 			columns: Vec::new(),
 			cells: Vec::new(),
+			borders: TableBorders::none(),
+			backgrounds: Backgrounds::default(),
+			vertical_aligns: HashMap::new(),
+			aligns: HashMap::new(),
 		}
 	}
 
@@ -72,6 +161,60 @@ impl<'a> TableBuilder<'a> {
 		self
 	}
 
+	/// Set the borders of the table.
+	pub fn borders(&mut self, borders: TableBorders) -> &mut Self {
+		self.borders = borders;
+		self
+	}
+
+	/// Set the background color of the cell starting at the given row and column.
+	///
+	/// Cell backgrounds are drawn on top of row and column backgrounds and cover all rows and columns spanned by the cell.
+	pub fn cell_background(&mut self, row: usize, column: usize, color: Color) -> &mut Self {
+		self.backgrounds.cells.insert((row, column), color);
+		self
+	}
+
+	/// Set the background color of a row.
+	///
+	/// The background covers the full width of the table, including positions without a cell.
+	pub fn row_background(&mut self, row: usize, color: Color) -> &mut Self {
+		self.backgrounds.rows.insert(row, color);
+		self
+	}
+
+	/// Set the background color of a column.
+	///
+	/// Row backgrounds and alternating rows take precedence over column backgrounds.
+	pub fn column_background(&mut self, column: usize, color: Color) -> &mut Self {
+		self.backgrounds.columns.insert(column, color);
+		self
+	}
+
+	/// Shade every other row with a background color, starting at `first_row`.
+	///
+	/// Explicit cell and row backgrounds take precedence over the alternating rows.
+	pub fn zebra_stripes(&mut self, color: Color, first_row: usize) -> &mut Self {
+		self.backgrounds.stripes = Some((color, first_row));
+		self
+	}
+
+	/// Set the vertical alignment of the cell starting at the given row and column.
+	///
+	/// This overrides the vertical alignment of the column.
+	pub fn cell_vertical_align(&mut self, row: usize, column: usize, align: VerticalAnchor) -> &mut Self {
+		self.vertical_aligns.insert((row, column), align);
+		self
+	}
+
+	/// Set the horizontal alignment of the cell starting at the given row and column.
+	///
+	/// This overrides the alignment of the column and the alignment of the text style of the cell.
+	pub fn cell_align(&mut self, row: usize, column: usize, align: CellAlign) -> &mut Self {
+		self.aligns.insert((row, column), align);
+		self
+	}
+
 	/// Set the columns of the table.
 	///
 	/// This replaces all existing column specifications with the given ones.
@@ -84,20 +227,42 @@ impl<'a> TableBuilder<'a> {
 	///
 	/// This replaces all existing column specifications with the given ones.
 	pub fn add_column(&mut self, grow: bool, max_width: Option<Length<Mm>>) -> &mut Self {
-		self.columns.push(ColumnSpec {
-			grow,
-			max_width,
-		});
+		self.columns.push(ColumnSpec::new(grow, max_width));
 		self
 	}
 
 	/// Add a cell to the table.
 	///
 	/// Cells must be added in row major order.
-	pub fn add_cell(&mut self, text: &str, style: &TextStyle) -> Result<&mut Self, String> {
-		let alignment = style.align;
+	pub fn add_cell<'t>(&mut self, text: impl Into<Text<'t>>, style: &TextStyle) -> Result<&mut Self, Error> {
+		self.add_spanning_cell(text, style, 1, 1)
+	}
+
+	/// Add a cell that spans multiple columns and/or rows.
+	///
+	/// Cells must be added in row major order.
+	/// Each cell is placed in the first grid position that is not occupied by an earlier cell,
+	/// so positions covered by a spanning cell are skipped automatically.
+	/// Column spans are limited to the remaining columns in the row.
+	pub fn add_spanning_cell<'t>(
+		&mut self,
+		text: impl Into<Text<'t>>,
+		style: &TextStyle,
+		column_span: usize,
+		row_span: usize,
+	) -> Result<&mut Self, Error> {
+		let alignment = style.align.into();
 		let text = self.pdf_writer.text_box(text, style, BoxPosition::at_xy(mm(0.0), mm(0.0)), None)?;
-		self.cells.push(TableCell { text, alignment });
+		self.cells.push(TableCell {
+			text,
+			alignment,
+			decimal_offset: None,
+			row: 0,
+			column: 0,
+			row_span: row_span.max(1),
+			column_span: column_span.max(1),
+			vertical_align: VerticalAnchor::Top,
+		});
 		Ok(self)
 	}
 
@@ -110,86 +275,228 @@ impl<'a> TableBuilder<'a> {
 pub struct ColumnSpec {
 	pub grow: bool,
 	pub max_width: Option<Length<Mm>>,
+
+	/// The horizontal alignment of the cells in the column.
+	///
+	/// If `None`, the alignment of the text style of each cell is used.
+	/// Only applies to cells that do not span multiple columns.
+	pub align: Option<CellAlign>,
+
+	/// The vertical alignment of the cells in the column.
+	///
+	/// With [`VerticalAnchor::Baseline`], the first baseline of all baseline-aligned cells in a row line up.
+	pub vertical_align: VerticalAnchor,
+}
+
+impl ColumnSpec {
+	/// Create a column specification with top-aligned cells.
+	pub fn new(grow: bool, max_width: Option<Length<Mm>>) -> Self {
+		Self {
+			grow,
+			max_width,
+			align: None,
+			vertical_align: VerticalAnchor::Top,
+		}
+	}
+
+	/// Set the horizontal alignment of the cells in the column.
+	pub fn align(self, align: CellAlign) -> Self {
+		Self {
+			align: Some(align),
+			..self
+		}
+	}
+
+	/// Set the vertical alignment of the cells in the column.
+	pub fn vertical_align(self, vertical_align: VerticalAnchor) -> Self {
+		Self {
+			vertical_align,
+			..self
+		}
+	}
 }
 
 impl Table {
-	pub fn new(builder: TableBuilder) -> Table {
+	pub fn new<W>(builder: TableBuilder<W>) -> Table {
 		let TableBuilder {
 			max_width,
 			position,
 			columns,
 			mut cells,
 			cell_padding,
+			borders,
+			backgrounds,
+			vertical_aligns,
+			aligns,
 			..
 		} = builder;
 
 		if columns.is_empty() || cells.is_empty() {
 			return Self {
+				origin: position.point,
 				position,
-				cell_padding,
 				columns: Vec::new(),
 				cells: Vec::new(),
 				column_widths: Vec::new(),
 				size: Size2::new(0.0, 0.0),
+				row_tops: vec![mm(0.0)],
+				grid: Vec::new(),
+				borders,
+				backgrounds,
 			}
 		}
 
 		let column_count = columns.len();
+		let (areas, grid) = place_cells(cells.iter().map(|cell| (cell.row_span, cell.column_span)), column_count);
+		let row_count = grid.len();
+		for (cell, area) in cells.iter_mut().zip(areas) {
+			cell.row = area.row;
+			cell.column = area.column;
+			cell.column_span = area.column_span;
+			cell.vertical_align = vertical_aligns.get(&(cell.row, cell.column))
+				.copied()
+				.unwrap_or(columns[cell.column].vertical_align);
+
+			let column_align = Some(columns[cell.column].align).filter(|_| cell.column_span == 1).flatten();
+			if let Some(align) = aligns.get(&(cell.row, cell.column)).copied().or(column_align) {
+				cell.alignment = align;
+			}
+			match cell.alignment {
+				CellAlign::Left => cell.text.layout.set_alignment(pango::Alignment::Left),
+				CellAlign::Center => cell.text.layout.set_alignment(pango::Alignment::Center),
+				CellAlign::Right => cell.text.layout.set_alignment(pango::Alignment::Right),
+				CellAlign::Decimal(separator) => {
+					cell.text.layout.set_alignment(pango::Alignment::Left);
+					cell.decimal_offset = Some(decimal_offset(&cell.text, separator));
+				},
+			}
+		}
+
+		// Compute the widths of the decimal aligned text before and after the separator for each column.
+		let mut decimal_widths = vec![(mm(0.0), mm(0.0)); column_count];
+		for cell in cells.iter().filter(|cell| cell.column_span == 1) {
+			if let Some(offset) = cell.decimal_offset {
+				let (before, after) = &mut decimal_widths[cell.column];
+				*before = before.max(offset);
+				*after = after.max(cell.text.logical_width() - offset);
+			}
+		}
 
 		// Compute maximum natural widths of the columns.
+		// Spanning cells are handled after all other cells, and only widen columns if the spanned columns are too narrow.
 		let mut natural_widths = vec![mm(0.0); column_count];
-		for (i, cell) in cells.iter().enumerate() {
-			let column = i % column_count;
+		for cell in cells.iter().filter(|cell| cell.column_span == 1) {
+			let column = cell.column;
 			natural_widths[column] = natural_widths[column].max(cell.text.logical_width() + cell_padding.total_horizontal());
 		}
+		for (natural, &(before, after)) in natural_widths.iter_mut().zip(&decimal_widths) {
+			*natural = natural.max(before + after + cell_padding.total_horizontal());
+		}
+		let mut spanning: Vec<_> = cells.iter().filter(|cell| cell.column_span > 1).collect();
+		spanning.sort_by_key(|cell| cell.column_span);
+		for cell in spanning {
+			let columns = cell.column..cell.column + cell.column_span;
+			let needed = cell.text.logical_width() + cell_padding.total_horizontal();
+			let available = natural_widths[columns.clone()].iter().sum::<Length<Mm>>();
+			if needed > available {
+				let extra = (needed - available) / cell.column_span as f64;
+				for width in &mut natural_widths[columns] {
+					*width += extra;
+				}
+			}
+		}
 
 		// Divide maximum width according to natural width.
 		let column_widths = divide_width(&columns, &natural_widths, max_width);
 
-		// Calculate the start of the text within each column.
-		// TODO: Deal with text alignment.
-		let mut column_inner_start = Vec::with_capacity(column_count);
+		// Calculate the start of each column.
+		let mut column_starts = Vec::with_capacity(column_count + 1);
 		let mut total_width = mm(0.0);
-		for (&width, &natural) in column_widths.iter().zip(&natural_widths) {
+		for &width in &column_widths {
+			column_starts.push(total_width);
 			total_width += width;
-			if natural < width {
-				column_inner_start.push((width - natural) * 0.5 + cell_padding.left);
-			} else {
-				column_inner_start.push(cell_padding.left);
-			}
+		}
+		column_starts.push(total_width);
+
+		// Wrap the text of all cells to the width of the spanned columns, except decimal aligned text.
+		for cell in cells.iter_mut().filter(|cell| cell.decimal_offset.is_none()) {
+			let outer_width = column_starts[cell.column + cell.column_span] - column_starts[cell.column];
+			cell.text.set_width(Some(outer_width - cell_padding.total_horizontal()));
+		}
+
+		// Find the lowest first baseline of the baseline-aligned cells in each row.
+		let mut row_baselines = vec![mm(0.0); row_count];
+		for cell in cells.iter().filter(|cell| cell.row_span == 1 && cell.vertical_align == VerticalAnchor::Baseline) {
+			row_baselines[cell.row] = row_baselines[cell.row].max(cell.text.baseline());
 		}
 
-		// Lay-out all cells in a table grid.
-		let mut cursor: Point2<Mm> = Point2::new(0.0, 0.0);
-		let mut row_height = mm(0.0);
-		for (i, cell) in cells.iter_mut().enumerate() {
-			let column = i % column_count;
-			if column == 0 {
-				cursor.x = 0.0;
-				cursor.y += (row_height + cell_padding.total_vertical()).get();
-				row_height = mm(0.0);
+		// Compute the row heights, again handling cells that span multiple rows last.
+		let mut row_heights = vec![mm(0.0); row_count];
+		for cell in cells.iter().filter(|cell| cell.row_span == 1) {
+			let row = cell.row;
+			let mut height = cell.text.logical_height() + cell_padding.total_vertical();
+			if cell.vertical_align == VerticalAnchor::Baseline {
+				height += row_baselines[row] - cell.text.baseline();
+			}
+			row_heights[row] = row_heights[row].max(height);
+		}
+		let mut spanning: Vec<_> = cells.iter().filter(|cell| cell.row_span > 1).collect();
+		spanning.sort_by_key(|cell| cell.row_span);
+		for cell in spanning {
+			let rows = cell.row..cell.row + cell.row_span;
+			let needed = cell.text.logical_height() + cell_padding.total_vertical();
+			let available = row_heights[rows.clone()].iter().sum::<Length<Mm>>();
+			if needed > available {
+				let extra = (needed - available) / cell.row_span as f64;
+				for height in &mut row_heights[rows] {
+					*height += extra;
+				}
 			}
+		}
+
+		let mut row_tops = Vec::with_capacity(row_count + 1);
+		let mut total_height = mm(0.0);
+		for &height in &row_heights {
+			row_tops.push(total_height);
+			total_height += height;
+		}
+		row_tops.push(total_height);
 
-			let outer_width = column_widths[column];
+		// Position all cells in the table grid.
+		for cell in &mut cells {
+			let outer_width = column_starts[cell.column + cell.column_span] - column_starts[cell.column];
 			let inner_width = outer_width - cell_padding.total_horizontal();
-			cell.text.set_width(Some(inner_width));
-
-			let inner_offset = Vector2::new(column_inner_start[column].get(), cell_padding.top.get());
-			row_height = row_height.max(cell.text.logical_height());
-			let text_position = match cell.alignment {
-				TextAlign::Left => BoxPosition::at(cursor + inner_offset),
-				TextAlign::Center => BoxPosition::at(cursor + inner_offset + Vector2::new(inner_width.get(), 0.0) * 0.5).anchor_middle_x(),
-				TextAlign::Right => BoxPosition::at(cursor + inner_offset + Vector2::new(inner_width.get(), 0.0)).anchor_right(),
+
+			let inner_height = row_tops[cell.row + cell.row_span] - row_tops[cell.row] - cell_padding.total_vertical();
+			let text_height = cell.text.logical_height();
+			let inner_top = match cell.vertical_align {
+				VerticalAnchor::Top => mm(0.0),
+				VerticalAnchor::Middle => (inner_height - text_height) * 0.5,
+				VerticalAnchor::Bottom => inner_height - text_height,
+				VerticalAnchor::Baseline if cell.row_span == 1 => row_baselines[cell.row] - cell.text.baseline(),
+				VerticalAnchor::Baseline => mm(0.0),
+			};
+
+			let cursor = Point2::new(column_starts[cell.column].get(), row_tops[cell.row].get());
+			let inner_offset = Vector2::new(cell_padding.left.get(), (cell_padding.top + inner_top).get());
+			let text_position = match (cell.alignment, cell.decimal_offset) {
+				(_, Some(offset)) if cell.column_span == 1 => {
+					// Put the separator at the same position for all decimal aligned cells in the column.
+					let (_, after) = decimal_widths[cell.column];
+					let x = inner_width - after - offset;
+					BoxPosition::at(cursor + inner_offset + Vector2::new(x.get(), 0.0))
+				},
+				(CellAlign::Center, _) => BoxPosition::at(cursor + inner_offset + Vector2::new(inner_width.get(), 0.0) * 0.5).anchor_middle_x(),
+				(CellAlign::Right, _) => BoxPosition::at(cursor + inner_offset + Vector2::new(inner_width.get(), 0.0)).anchor_right(),
+				(CellAlign::Left, _) | (CellAlign::Decimal(_), _) => BoxPosition::at(cursor + inner_offset),
 			};
 			cell.text.set_position(text_position);
-			cursor.x += column_widths[column].get();
 		}
 
-		cursor.y += row_height.get();
-		let size = Size2::new(total_width.get(), cursor.y);
+		let size = Size2::new(total_width.get(), total_height.get());
 
 		let baseline = cells
-			.get(0)
+			.first()
 			.map(|cell| cell.text.baseline())
 			.unwrap_or(mm(0.0));
 		let offset = position.point.to_vector() + position.alignment_offset(size, baseline);
@@ -199,34 +506,236 @@ impl Table {
 
 		Self {
 			position,
-			cell_padding,
 			columns,
 			cells,
 			column_widths,
 			size,
+			origin: Point2::origin() + offset,
+			row_tops,
+			grid,
+			borders,
+			backgrounds,
 		}
 	}
 
 	/// Get the baseline of a table row.
 	///
-	/// The baseline is computed based on the first line of text in the first cell of the row.
+	/// The baseline is computed based on the first line of text in the first cell that starts in the row.
+	/// If the row is completely covered by cells spanning from earlier rows, the top of the row is used instead.
+	///
+	/// # Panics
+	/// This function panics if the row index is out of bounds.
 	pub fn baseline(&self, row: usize) -> Length<Mm> {
-		let cell = &self.cells[row * self.columns.len()];
-		mm(cell.text.compute_extents().logical.min.y) + cell.text.baseline()
+		assert!(row < self.rows(), "row index out of bounds");
+		match self.cells.iter().find(|cell| cell.row == row) {
+			Some(cell) => mm(cell.text.compute_extents().logical.min.y) + cell.text.baseline(),
+			None => mm(self.origin.y) + self.row_tops[row],
+		}
 	}
 
 	pub fn draw(&self, page: &Page) {
-		for cell in &self.cells {
-			cell.text.draw(page);
+		let rows: Vec<_> = (0..self.rows()).collect();
+		self.draw_row_list(page, &rows, mm(self.origin.y));
+	}
+
+	/// Get the height of a range of rows, including cell padding.
+	pub fn rows_height(&self, rows: std::ops::Range<usize>) -> Length<Mm> {
+		self.row_tops[rows.end] - self.row_tops[rows.start]
+	}
+
+	/// Draw a range of rows with the top of the first row at the given vertical position.
+	///
+	/// The horizontal position of the table is not changed.
+	/// Returns the height of the drawn rows.
+	pub fn draw_rows(&self, page: &Page, rows: std::ops::Range<usize>, y: Length<Mm>) -> Length<Mm> {
+		let rows: Vec<_> = rows.collect();
+		self.draw_row_list(page, &rows, y)
+	}
+
+	/// Draw a list of rows below each other, with the top of the first row at the given vertical position.
+	///
+	/// The borders are drawn as if the rows form a table on their own.
+	fn draw_row_list(&self, page: &Page, rows: &[usize], y: Length<Mm>) -> Length<Mm> {
+		let mut tops = Vec::with_capacity(rows.len() + 1);
+		let mut top = y;
+		for &row in rows {
+			tops.push(top);
+			top += self.rows_height(row..row + 1);
+		}
+		tops.push(top);
+
+		// Row, stripe and column backgrounds are painted as bands across the full table,
+		// including grid positions without a cell. Runs of equal color are merged to avoid seams.
+		let column_count = self.columns.len();
+		for (&row, (&top, &bottom)) in rows.iter().zip(tops.iter().zip(&tops[1..])) {
+			let mut column = 0;
+			while column < column_count {
+				let color = self.backgrounds.band(row, column);
+				let end = (column + 1..column_count)
+					.find(|&x| self.backgrounds.band(row, x) != color)
+					.unwrap_or(column_count);
+				if let Some(color) = color {
+					let x1 = self.get_column_start(column);
+					let x2 = self.get_column_end(end - 1);
+					let area = Box2::new(Point2::new(x1.get(), top.get()), Point2::new(x2.get(), bottom.get()));
+					page.draw_rectangle(area, &ShapeStyle::fill(color));
+				}
+				column = end;
+			}
+		}
+
+		// Cell backgrounds are painted on top, covering the full area of spanning cells.
+		for (&row, &top) in rows.iter().zip(&tops) {
+			for column in 0..column_count {
+				let cell = match self.cell_starting_at(row, column) {
+					Some(x) => x,
+					None => continue,
+				};
+				if let Some(&color) = self.backgrounds.cells.get(&(row, column)) {
+					let x1 = self.get_column_start(column);
+					let x2 = self.get_column_end(column + cell.column_span - 1);
+					let y2 = top + self.rows_height(row..row + cell.row_span);
+					let area = Box2::new(Point2::new(x1.get(), top.get()), Point2::new(x2.get(), y2.get()));
+					page.draw_rectangle(area, &ShapeStyle::fill(color));
+				}
+			}
+		}
+
+		for (&row, &top) in rows.iter().zip(&tops) {
+			let offset = Vector2::new(0.0, (top - self.row_tops[row]).get() - self.origin.y);
+			for column in 0..self.columns.len() {
+				if let Some(cell) = self.cell_starting_at(row, column) {
+					cell.text.draw_offset(page, offset);
+				}
+			}
+		}
+
+		if !self.borders.is_empty() {
+			self.draw_borders(page, rows, &tops);
 		}
+		top - y
 	}
 
-	pub fn draw_horizontal_border<R: std::ops::RangeBounds<usize>>(&self, page: &Page, row: usize, columns: R, width: Length<Pt>) {
-		let y = if row == self.rows() {
-			mm(self.size.height)
+	/// Draw the borders for a list of rows, given the top of each row and the bottom of the last row.
+	fn draw_borders(&self, page: &Page, rows: &[usize], tops: &[Length<Mm>]) {
+		let column_count = self.columns.len();
+		let position = |cell: Option<usize>| cell.map(|i| (self.cells[i].row, self.cells[i].column));
+
+		for (i, &y) in tops.iter().enumerate() {
+			let above_row = i.checked_sub(1).map(|i| rows[i]);
+			let below_row = rows.get(i).copied();
+			for column in 0..column_count {
+				let above = above_row.and_then(|row| self.grid[row][column]);
+				let below = below_row.and_then(|row| self.grid[row][column]);
+				if above.is_some() && above == below {
+					continue;
+				}
+				let line = self.borders.resolve_horizontal(position(above), position(below), above_row, below_row, column);
+				if let Some(line) = line {
+					let x1 = self.get_column_start(column);
+					let x2 = self.get_column_end(column);
+					page.draw_line(Point2::new(x1.get(), y.get()), Point2::new(x2.get(), y.get()), line);
+				}
+			}
+		}
+
+		for (i, &row) in rows.iter().enumerate() {
+			for boundary in 0..=column_count {
+				let left_column = boundary.checked_sub(1);
+				let right_column = Some(boundary).filter(|&x| x < column_count);
+				let left = left_column.and_then(|column| self.grid[row][column]);
+				let right = right_column.and_then(|column| self.grid[row][column]);
+				if left.is_some() && left == right {
+					continue;
+				}
+				let line = self.borders.resolve_vertical(position(left), position(right), left_column, right_column, row);
+				if let Some(line) = line {
+					let x = match right_column {
+						Some(column) => self.get_column_start(column),
+						None => self.get_column_end(column_count - 1),
+					};
+					page.draw_line(Point2::new(x.get(), tops[i].get()), Point2::new(x.get(), tops[i + 1].get()), line);
+				}
+			}
+		}
+	}
+
+	/// Get the cell that starts at the given grid position, if any.
+	fn cell_starting_at(&self, row: usize, column: usize) -> Option<&TableCell> {
+		let cell = &self.cells[self.grid[row][column]?];
+		if cell.row == row && cell.column == column {
+			Some(cell)
 		} else {
-			mm(self.cells[row * self.columns.len()].text.position().point.y) - self.cell_padding.top
-		};
+			None
+		}
+	}
+
+	/// Get the first row boundary after `row` where the table can be split.
+	///
+	/// The table can not be split between rows that are spanned by a single cell.
+	fn next_break(&self, row: usize) -> usize {
+		let mut end = row + 1;
+		loop {
+			let spanned = self.cells.iter()
+				.filter(|cell| cell.row < end && cell.row + cell.row_span > end)
+				.map(|cell| cell.row + cell.row_span)
+				.max();
+			match spanned {
+				Some(x) => end = x,
+				None => return end,
+			}
+		}
+	}
+
+	/// Draw as many rows as fit on the page, starting at the cursor.
+	///
+	/// Drawing starts at `start_row`, and the cursor of the page is advanced past the drawn rows.
+	/// Rows spanned by a single cell are kept together.
+	/// The first `header_rows` rows are treated as header.
+	/// When continuing a table (`start_row` is past the header rows), the header rows are drawn again first.
+	/// The horizontal position of the table is taken from the table position,
+	/// the vertical position of the table is ignored.
+	///
+	/// Nothing is drawn if the header and the first row do not fit,
+	/// unless the cursor is already at the top of the page.
+	///
+	/// Returns the index of the first row that did not fit, or `None` if all rows were drawn.
+	pub fn draw_partial(&self, page: &mut Page, start_row: usize, header_rows: usize) -> Option<usize> {
+		let rows = self.rows();
+		let header_rows = header_rows.min(rows);
+		let body_start = start_row.max(header_rows);
+		if body_start >= rows && start_row > 0 {
+			return None;
+		}
+
+		let header_height = self.rows_height(0..header_rows);
+		let available = page.text_bottom() - page.cursor_y - header_height;
+		let mut body_end = body_start;
+		while body_end < rows && self.rows_height(body_start..self.next_break(body_end)) <= available {
+			body_end = self.next_break(body_end);
+		}
+
+		// Always make progress at the top of a page, even if the rows are too high for the page.
+		if body_end == body_start && body_start < rows {
+			if page.cursor_y > page.margins.top {
+				return Some(start_row);
+			}
+			body_end = self.next_break(body_end);
+		}
+
+		let drawn: Vec<_> = (0..header_rows).chain(body_start..body_end).collect();
+		let height = self.draw_row_list(page, &drawn, page.cursor_y);
+		page.cursor_y += height;
+
+		if body_end < rows {
+			Some(body_end)
+		} else {
+			None
+		}
+	}
+
+	pub fn draw_horizontal_border<R: std::ops::RangeBounds<usize>>(&self, page: &Page, row: usize, columns: R, width: Length<Pt>) {
+		let y = mm(self.origin.y) + self.row_tops[row];
 
 		let x1 = match columns.start_bound() {
 			std::ops::Bound::Included(&i) => i,
@@ -245,24 +754,15 @@ impl Table {
 		let x1 = self.get_column_start(x1);
 		let x2 = self.get_column_end(x2);
 
-		let y = y * PT_PER_MM;
-		let x1 = x1 * PT_PER_MM;
-		let x2 = x2 * PT_PER_MM;
-		page.cairo.save();
-		page.cairo.move_to(x1.get(), y.get());
-		page.cairo.line_to(x2.get(), y.get());
-		page.cairo.set_line_width(width.get());
-		page.cairo.set_source(&cairo::SolidPattern::from_rgba(0.0, 0.0, 0.0, 1.0));
-		page.cairo.stroke();
-		page.cairo.restore();
+		page.draw_line(
+			Point2::new(x1.get(), y.get()),
+			Point2::new(x2.get(), y.get()),
+			&StrokeStyle::new(width, Color::BLACK),
+		);
 	}
 
 	pub fn rows(&self) -> usize {
-		if self.cells.is_empty() {
-			0
-		} else {
-			(self.cells.len() + self.columns.len() - 1) / self.columns.len()
-		}
+		self.row_tops.len() - 1
 	}
 
 	fn get_column_start(&self, index: usize) -> Length<Mm> {
@@ -281,6 +781,95 @@ impl Table {
 	}
 }
 
+impl Page {
+	/// Write a table at the cursor, continuing on new pages as needed.
+	///
+	/// Whenever the table does not fit on the current page, the page is emitted and the table continues on a fresh page.
+	/// The first `header_rows` rows of the table are repeated at the top of each continuation.
+	/// See [`Table::draw_partial`] for details.
+	pub fn write_table<W>(&mut self, pdf: &PdfWriter<W>, table: &Table, header_rows: usize) -> Result<(), Error> {
+		let mut next_row = 0;
+		while let Some(row) = table.draw_partial(self, next_row, header_rows) {
+			next_row = row;
+			self.next_page(pdf)?;
+		}
+		Ok(())
+	}
+}
+
+/// Get the width of the text before the first decimal separator.
+///
+/// If the text does not contain the separator, the full width of the text is returned.
+fn decimal_offset(text: &TextBox, separator: char) -> Length<Mm> {
+	let (_, logical) = text.layout.get_extents();
+	let index = text.layout.get_text()
+		.and_then(|text| text.find(separator));
+	match index {
+		Some(index) => {
+			let position = text.layout.index_to_pos(index as i32);
+			crate::mm_from_pango(position.x - logical.x)
+		},
+		None => text.logical_width(),
+	}
+}
+
+/// The area of the table grid covered by a cell.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct GridArea {
+	row: usize,
+	column: usize,
+	row_span: usize,
+	column_span: usize,
+}
+
+/// Assign grid positions to cells in row major order, skipping positions covered by spanning cells.
+///
+/// Takes the row and column span of each cell.
+/// Column spans are clamped to stop at the edge of the table or at the first column that is already occupied.
+///
+/// Returns the area covered by each cell, and the index of the cell covering each grid position.
+fn place_cells(spans: impl IntoIterator<Item = (usize, usize)>, column_count: usize) -> (Vec<GridArea>, Vec<Vec<Option<usize>>>) {
+	let mut areas = Vec::new();
+	let mut occupied: Vec<Vec<Option<usize>>> = Vec::new();
+	let mut row = 0;
+	let mut column = 0;
+	for (index, (row_span, column_span)) in spans.into_iter().enumerate() {
+		loop {
+			if column == column_count {
+				row += 1;
+				column = 0;
+			}
+			if !matches!(occupied.get(row), Some(x) if x[column].is_some()) {
+				break;
+			}
+			column += 1;
+		}
+
+		// Cells spanning from earlier rows into the rows of this cell also cover this row,
+		// so only the first row needs to be checked.
+		let free = occupied.get(row).map_or(column_count - column, |x| {
+			x[column..].iter().take_while(|x| x.is_none()).count()
+		});
+		let area = GridArea {
+			row,
+			column,
+			row_span,
+			column_span: column_span.min(free),
+		};
+		if occupied.len() < row + row_span {
+			occupied.resize(row + row_span, vec![None; column_count]);
+		}
+		for occupied in &mut occupied[row..row + row_span] {
+			for x in &mut occupied[column..column + area.column_span] {
+				*x = Some(index);
+			}
+		}
+		column += area.column_span;
+		areas.push(area);
+	}
+	(areas, occupied)
+}
+
 fn divide_width<U>(columns: &[ColumnSpec], natural_widths: &[Length<U>], available_width: Length<U>) -> Vec<Length<U>> {
 	debug_assert!(columns.len() == natural_widths.len());
 
@@ -329,3 +918,63 @@ fn divide_width<U>(columns: &[ColumnSpec], natural_widths: &[Length<U>], availab
 
 	widths
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn area(row: usize, column: usize, row_span: usize, column_span: usize) -> GridArea {
+		GridArea { row, column, row_span, column_span }
+	}
+
+	#[test]
+	fn place_simple_cells() {
+		let (areas, grid) = place_cells(vec![(1, 1); 5], 2);
+		assert_eq!(areas, [area(0, 0, 1, 1), area(0, 1, 1, 1), area(1, 0, 1, 1), area(1, 1, 1, 1), area(2, 0, 1, 1)]);
+		assert_eq!(grid, [
+			[Some(0), Some(1)],
+			[Some(2), Some(3)],
+			[Some(4), None],
+		]);
+	}
+
+	#[test]
+	fn place_spanning_cells() {
+		let (areas, grid) = place_cells(vec![(2, 1), (1, 2), (1, 1), (1, 1)], 3);
+		assert_eq!(areas, [area(0, 0, 2, 1), area(0, 1, 1, 2), area(1, 1, 1, 1), area(1, 2, 1, 1)]);
+		assert_eq!(grid, [
+			[Some(0), Some(1), Some(1)],
+			[Some(0), Some(2), Some(3)],
+		]);
+	}
+
+	#[test]
+	fn clamp_column_span_at_table_edge() {
+		let (areas, grid) = place_cells(vec![(1, 1), (1, 5)], 3);
+		assert_eq!(areas, [area(0, 0, 1, 1), area(0, 1, 1, 2)]);
+		assert_eq!(grid, [[Some(0), Some(1), Some(1)]]);
+	}
+
+	#[test]
+	fn clamp_column_span_at_occupied_column() {
+		let (areas, grid) = place_cells(vec![(1, 1), (1, 1), (2, 1), (1, 3), (1, 1)], 3);
+		assert_eq!(areas, [area(0, 0, 1, 1), area(0, 1, 1, 1), area(0, 2, 2, 1), area(1, 0, 1, 2), area(2, 0, 1, 1)]);
+		assert_eq!(grid, [
+			[Some(0), Some(1), Some(2)],
+			[Some(3), Some(3), Some(2)],
+			[Some(4), None, None],
+		]);
+	}
+
+	#[test]
+	fn skip_fully_covered_rows() {
+		let (areas, grid) = place_cells(vec![(3, 2), (1, 1)], 2);
+		assert_eq!(areas, [area(0, 0, 3, 2), area(3, 0, 1, 1)]);
+		assert_eq!(grid, [
+			[Some(0), Some(0)],
+			[Some(0), Some(0)],
+			[Some(0), Some(0)],
+			[Some(1), None],
+		]);
+	}
+}
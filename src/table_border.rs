@@ -0,0 +1,258 @@
+use std::collections::HashMap;
+
+use crate::{
+	Color,
+	StrokeStyle,
+	pt,
+};
+
+/// The borders of a table.
+///
+/// The default borders are used for the outer frame and the inner rules between rows and columns.
+/// They can be overridden for individual rows, columns and cells.
+/// When overrides conflict, cell overrides take precedence over row overrides,
+/// and row overrides take precedence over column overrides.
+///
+/// The default value has no borders at all.
+#[derive(Debug, Clone, Default)]
+pub struct TableBorders {
+	/// The line above the first row.
+	pub top: Option<StrokeStyle>,
+
+	/// The line below the last row.
+	pub bottom: Option<StrokeStyle>,
+
+	/// The line left of the first column.
+	pub left: Option<StrokeStyle>,
+
+	/// The line right of the last column.
+	pub right: Option<StrokeStyle>,
+
+	/// The lines between rows.
+	pub horizontal: Option<StrokeStyle>,
+
+	/// The lines between columns.
+	pub vertical: Option<StrokeStyle>,
+
+	rows: HashMap<usize, BorderOverride>,
+	columns: HashMap<usize, BorderOverride>,
+	cells: HashMap<(usize, usize), BorderOverride>,
+}
+
+/// Border overrides for the sides of a row, column or cell.
+///
+/// Sides that are not set use the borders of the table.
+#[derive(Debug, Clone, Default)]
+pub struct BorderOverride {
+	top: Option<Option<StrokeStyle>>,
+	bottom: Option<Option<StrokeStyle>>,
+	left: Option<Option<StrokeStyle>>,
+	right: Option<Option<StrokeStyle>>,
+}
+
+impl TableBorders {
+	/// Create a table without borders.
+	pub fn none() -> Self {
+		Self::default()
+	}
+
+	/// Create a full grid with the same line for the frame and all inner rules.
+	pub fn grid(line: StrokeStyle) -> Self {
+		Self {
+			top: Some(line.clone()),
+			bottom: Some(line.clone()),
+			left: Some(line.clone()),
+			right: Some(line.clone()),
+			horizontal: Some(line.clone()),
+			vertical: Some(line),
+			..Self::default()
+		}
+	}
+
+	/// Create only an outer frame around the table.
+	pub fn frame(line: StrokeStyle) -> Self {
+		Self {
+			top: Some(line.clone()),
+			bottom: Some(line.clone()),
+			left: Some(line.clone()),
+			right: Some(line),
+			..Self::default()
+		}
+	}
+
+	/// Create booktabs-style borders.
+	///
+	/// This draws a heavy rule above and below the table,
+	/// and a light rule below the first `header_rows` rows.
+	/// There are no vertical lines.
+	pub fn booktabs(header_rows: usize) -> Self {
+		let heavy = StrokeStyle::new(pt(0.8), Color::BLACK);
+		let light = StrokeStyle::new(pt(0.5), Color::BLACK);
+		let borders = Self {
+			top: Some(heavy.clone()),
+			bottom: Some(heavy),
+			..Self::default()
+		};
+		if header_rows == 0 {
+			borders
+		} else {
+			borders.with_row(header_rows - 1, BorderOverride::new().bottom(Some(light)))
+		}
+	}
+
+	/// Override the borders of a row.
+	///
+	/// The left and right side apply to the start and end of the row.
+	pub fn with_row(mut self, row: usize, borders: BorderOverride) -> Self {
+		self.rows.insert(row, borders);
+		self
+	}
+
+	/// Override the borders of a column.
+	///
+	/// The top and bottom side apply to the top and bottom of the column.
+	pub fn with_column(mut self, column: usize, borders: BorderOverride) -> Self {
+		self.columns.insert(column, borders);
+		self
+	}
+
+	/// Override the borders of the cell starting at the given row and column.
+	pub fn with_cell(mut self, row: usize, column: usize, borders: BorderOverride) -> Self {
+		self.cells.insert((row, column), borders);
+		self
+	}
+
+	/// Check if the table has any borders at all.
+	pub(crate) fn is_empty(&self) -> bool {
+		self.top.is_none()
+			&& self.bottom.is_none()
+			&& self.left.is_none()
+			&& self.right.is_none()
+			&& self.horizontal.is_none()
+			&& self.vertical.is_none()
+			&& self.rows.is_empty()
+			&& self.columns.is_empty()
+			&& self.cells.is_empty()
+	}
+
+	/// Resolve the line between two vertically adjacent cells.
+	///
+	/// The cells are given as the grid position where they start,
+	/// or `None` at the top or bottom edge of the drawn rows.
+	pub(crate) fn resolve_horizontal(
+		&self,
+		above: Option<(usize, usize)>,
+		below: Option<(usize, usize)>,
+		above_row: Option<usize>,
+		below_row: Option<usize>,
+		column: usize,
+	) -> Option<&StrokeStyle> {
+		let overrides = [
+			below.and_then(|x| self.cells.get(&x)).and_then(|x| x.top.as_ref()),
+			above.and_then(|x| self.cells.get(&x)).and_then(|x| x.bottom.as_ref()),
+			below_row.and_then(|x| self.rows.get(&x)).and_then(|x| x.top.as_ref()),
+			above_row.and_then(|x| self.rows.get(&x)).and_then(|x| x.bottom.as_ref()),
+			self.columns.get(&column).and_then(|x| match (above_row, below_row) {
+				(None, _) => x.top.as_ref(),
+				(_, None) => x.bottom.as_ref(),
+				_ => None,
+			}),
+		];
+		let default = match (above_row, below_row) {
+			(None, _) => &self.top,
+			(_, None) => &self.bottom,
+			_ => &self.horizontal,
+		};
+		overrides.iter().flatten().next().copied().unwrap_or(default).as_ref()
+	}
+
+	/// Resolve the line between two horizontally adjacent cells.
+	///
+	/// The cells are given as the grid position where they start,
+	/// or `None` at the left or right edge of the table.
+	pub(crate) fn resolve_vertical(
+		&self,
+		left: Option<(usize, usize)>,
+		right: Option<(usize, usize)>,
+		left_column: Option<usize>,
+		right_column: Option<usize>,
+		row: usize,
+	) -> Option<&StrokeStyle> {
+		let overrides = [
+			right.and_then(|x| self.cells.get(&x)).and_then(|x| x.left.as_ref()),
+			left.and_then(|x| self.cells.get(&x)).and_then(|x| x.right.as_ref()),
+			self.rows.get(&row).and_then(|x| match (left_column, right_column) {
+				(None, _) => x.left.as_ref(),
+				(_, None) => x.right.as_ref(),
+				_ => None,
+			}),
+			right_column.and_then(|x| self.columns.get(&x)).and_then(|x| x.left.as_ref()),
+			left_column.and_then(|x| self.columns.get(&x)).and_then(|x| x.right.as_ref()),
+		];
+		let default = match (left_column, right_column) {
+			(None, _) => &self.left,
+			(_, None) => &self.right,
+			_ => &self.vertical,
+		};
+		overrides.iter().flatten().next().copied().unwrap_or(default).as_ref()
+	}
+}
+
+impl BorderOverride {
+	/// Create an empty override that uses the borders of the table for all sides.
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Set the line on all sides.
+	///
+	/// Use `None` to remove the lines.
+	pub fn all(self, line: Option<StrokeStyle>) -> Self {
+		Self {
+			top: Some(line.clone()),
+			bottom: Some(line.clone()),
+			left: Some(line.clone()),
+			right: Some(line),
+		}
+	}
+
+	/// Set the line at the top.
+	///
+	/// Use `None` to remove the line.
+	pub fn top(self, line: Option<StrokeStyle>) -> Self {
+		Self {
+			top: Some(line),
+			..self
+		}
+	}
+
+	/// Set the line at the bottom.
+	///
+	/// Use `None` to remove the line.
+	pub fn bottom(self, line: Option<StrokeStyle>) -> Self {
+		Self {
+			bottom: Some(line),
+			..self
+		}
+	}
+
+	/// Set the line on the left side.
+	///
+	/// Use `None` to remove the line.
+	pub fn left(self, line: Option<StrokeStyle>) -> Self {
+		Self {
+			left: Some(line),
+			..self
+		}
+	}
+
+	/// Set the line on the right side.
+	///
+	/// Use `None` to remove the line.
+	pub fn right(self, line: Option<StrokeStyle>) -> Self {
+		Self {
+			right: Some(line),
+			..self
+		}
+	}
+}
@@ -1,8 +1,14 @@
+#[cfg(feature = "overline")]
+use std::os::raw::{c_int, c_void};
+
 use crate::{
+	Color,
+	Error,
 	FontSpec,
 	Length,
 	Mm,
 	Point2,
+	Pt,
 	Size2,
 	Vector2,
 };
@@ -13,23 +19,212 @@ pub struct TextStyle {
 	pub align: TextAlign,
 	pub justify: bool,
 	pub line_height: f64,
+
+	/// The color of the text.
+	pub color: Color,
+
+	/// The background color behind the text, if any.
+	pub background: Option<Color>,
+
+	/// Extra space added between letters.
+	pub letter_spacing: Length<Pt>,
+
+	/// Extra space added between words, on top of the letter spacing.
+	///
+	/// Pango has no word spacing attribute, so this is applied as letter spacing of the space characters.
+	pub word_spacing: Length<Pt>,
+
+	/// The line drawn below the text.
+	pub underline: Underline,
+
+	/// The color of the underline, or `None` to use the text color.
+	pub underline_color: Option<Color>,
+
+	/// Draw a line through the text.
+	pub strikethrough: bool,
+
+	/// The color of the strikethrough line, or `None` to use the text color.
+	pub strikethrough_color: Option<Color>,
+
+	/// Draw a line above the text.
+	///
+	/// Overlines are only drawn with the `overline` feature, which requires pango 1.46 or newer.
+	/// Without the feature, this field is ignored.
+	pub overline: bool,
+
+	/// The color of the overline, or `None` to use the text color.
+	pub overline_color: Option<Color>,
+}
+
+impl Default for TextStyle {
+	/// Get a plain, left aligned style with black text in a 10 point sans-serif font.
+	fn default() -> Self {
+		Self {
+			font: FontSpec::default(),
+			align: TextAlign::Left,
+			justify: false,
+			line_height: 1.0,
+			color: Color::BLACK,
+			background: None,
+			letter_spacing: crate::pt(0.0),
+			word_spacing: crate::pt(0.0),
+			underline: Underline::None,
+			underline_color: None,
+			strikethrough: false,
+			strikethrough_color: None,
+			overline: false,
+			overline_color: None,
+		}
+	}
 }
 
 impl TextStyle {
-	pub(crate) fn apply_to_layout(&self, layout: &pango::Layout) {
-		let font = self.font.to_pango();
-		layout.set_font_description(Some(&font));
+	/// Apply the style to a layout.
+	///
+	/// The text attributes are merged with the attributes of the style.
+	/// Text attributes take precedence over style attributes.
+	///
+	/// The font description must be the font of the style with the family resolved to a font known to the font map.
+	/// The text color is not part of the layout, it is applied when drawing the text.
+	pub(crate) fn apply_to_layout(
+		&self,
+		layout: &pango::Layout,
+		font: &pango::FontDescription,
+		text_attributes: Option<&pango::AttrList>,
+	) -> Result<(), Error> {
+		layout.set_font_description(Some(font));
 		layout.set_alignment(self.align.to_pango());
 		layout.set_justify(self.justify);
 
 		let spacing = self.font.size * (self.line_height - 1.0);
 		layout.set_spacing((spacing * crate::PANGO_PER_PT).get().round() as i32);
+
+		let attributes = text_attributes
+			.and_then(|x| x.copy())
+			.unwrap_or_default();
+		for attribute in self.attributes()? {
+			attributes.insert_before(attribute);
+		}
+		if self.word_spacing.get() != 0.0 {
+			let spacing = ((self.letter_spacing + self.word_spacing) * crate::PANGO_PER_PT).get().round() as i32;
+			let text = layout.get_text()
+				.ok_or(Error::CreateLayout)?;
+			for (index, c) in text.char_indices() {
+				if c == ' ' || c == '\u{A0}' {
+					let mut attribute = pango::Attribute::new_letter_spacing(spacing)
+						.ok_or(Error::CreateLayout)?;
+					attribute.set_start_index(index as u32);
+					attribute.set_end_index((index + c.len_utf8()) as u32);
+					attributes.insert(attribute);
+				}
+			}
+		}
+		layout.set_attributes(Some(&attributes));
+		Ok(())
+	}
+
+	/// Get the pango attributes for the style that cover the whole text.
+	fn attributes(&self) -> Result<Vec<pango::Attribute>, Error> {
+		let mut attributes = Vec::new();
+		if let Some(features) = self.font.features_string() {
+			attributes.push(pango::Attribute::new_font_features(&features));
+		}
+		if let Some(background) = self.background {
+			let (red, green, blue) = background.to_pango_rgb();
+			attributes.push(pango::Attribute::new_background(red, green, blue));
+			attributes.push(pango::Attribute::new_background_alpha(background.to_pango_alpha()));
+		}
+		if self.letter_spacing.get() != 0.0 {
+			let spacing = (self.letter_spacing * crate::PANGO_PER_PT).get().round() as i32;
+			attributes.push(pango::Attribute::new_letter_spacing(spacing));
+		}
+		if self.underline != Underline::None {
+			attributes.push(pango::Attribute::new_underline(self.underline.to_pango()));
+			if let Some(color) = self.underline_color {
+				let (red, green, blue) = color.to_pango_rgb();
+				attributes.push(pango::Attribute::new_underline_color(red, green, blue));
+			}
+		}
+		if self.strikethrough {
+			attributes.push(pango::Attribute::new_strikethrough(true));
+			if let Some(color) = self.strikethrough_color {
+				let (red, green, blue) = color.to_pango_rgb();
+				attributes.push(pango::Attribute::new_strikethrough_color(red, green, blue));
+			}
+		}
+		#[cfg(feature = "overline")]
+		if self.overline {
+			attributes.push(new_overline());
+			if let Some(color) = self.overline_color {
+				attributes.push(new_overline_color(color));
+			}
+		}
+
+		attributes.into_iter()
+			.map(|attribute| attribute.ok_or(Error::CreateLayout))
+			.collect()
 	}
 }
 
+/// The style of the line drawn below text.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum Underline {
+	#[default]
+	None,
 
-#[derive(Debug, Copy, Clone)]
+	/// A single line.
+	Single,
+
+	/// A double line.
+	Double,
+
+	/// A wavy line, as used to indicate spelling errors.
+	Error,
+}
+
+impl Underline {
+	pub(crate) fn to_pango(self) -> pango::Underline {
+		match self {
+			Self::None => pango::Underline::None,
+			Self::Single => pango::Underline::Single,
+			Self::Double => pango::Underline::Double,
+			Self::Error => pango::Underline::Error,
+		}
+	}
+}
+
+#[cfg(feature = "overline")]
+/// Create an overline attribute, which the pango bindings don't expose yet.
+fn new_overline() -> Option<pango::Attribute> {
+	unsafe {
+		let attribute = pango_attr_overline_new(PANGO_OVERLINE_SINGLE);
+		(!attribute.is_null()).then(|| glib::translate::from_glib_full(attribute as *mut _))
+	}
+}
+
+#[cfg(feature = "overline")]
+/// Create an overline color attribute, which the pango bindings don't expose yet.
+fn new_overline_color(color: Color) -> Option<pango::Attribute> {
+	let (red, green, blue) = color.to_pango_rgb();
+	unsafe {
+		let attribute = pango_attr_overline_color_new(red, green, blue);
+		(!attribute.is_null()).then(|| glib::translate::from_glib_full(attribute as *mut _))
+	}
+}
+
+#[cfg(feature = "overline")]
+const PANGO_OVERLINE_SINGLE: c_int = 1;
+
+// Linked through pkg-config by the build script, which checks for pango 1.46 or newer.
+#[cfg(feature = "overline")]
+extern "C" {
+	fn pango_attr_overline_new(overline: c_int) -> *mut c_void;
+	fn pango_attr_overline_color_new(red: u16, green: u16, blue: u16) -> *mut c_void;
+}
+
+#[derive(Debug, Copy, Clone, Default)]
 pub enum TextAlign {
+	#[default]
 	Left,
 	Center,
 	Right,
@@ -45,12 +240,6 @@ impl TextAlign {
 	}
 }
 
-impl std::default::Default for TextAlign {
-	fn default() -> Self {
-		Self::Left
-	}
-}
-
 #[derive(Debug, Clone)]
 pub struct BoxPosition {
 	pub point: Point2<Mm>,
@@ -124,21 +313,21 @@ impl BoxPosition {
 		let x = match self.anchor_h {
 			HorizontalAnchor::Left => 0.0,
 			HorizontalAnchor::Middle => size.width * -0.5,
-			HorizontalAnchor::Right => size.width * -1.0,
+			HorizontalAnchor::Right => -size.width,
 		};
 
 		let y = match self.anchor_v {
 			VerticalAnchor::Top => 0.0,
 			VerticalAnchor::Baseline => -baseline.get(),
 			VerticalAnchor::Middle => size.height * -0.5,
-			VerticalAnchor::Bottom => size.height * -1.0,
+			VerticalAnchor::Bottom => -size.height,
 		};
 
 		Vector2::new(x, y)
 	}
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum VerticalAnchor {
 	Top,
 	Baseline,
@@ -29,6 +29,15 @@ pub struct Margins<T> {
 	pub right: Length<T>,
 }
 
+// Implemented manually, since deriving would require the unit to implement `Copy` too.
+impl<T> Copy for Margins<T> {}
+
+impl<T> Clone for Margins<T> {
+	fn clone(&self) -> Self {
+		*self
+	}
+}
+
 impl<T> Margins<T> {
 	/// Create a new `Margins` object from the individual top, bottom left and right margin.
 	pub fn tblr(top: Length<T>, bottom: Length<T>, left: Length<T>, right: Length<T>) -> Self {